@@ -1,7 +1,17 @@
-use crate::{literal::Literal, operator::Operator};
+use std::fmt;
+
+use error::span::Span;
+
+use crate::{literal::Literal, operator::Operator, resolution::Resolution, statement::Statement};
+
+#[derive(Debug)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
 
 #[derive(Debug)]
-pub enum Expression {
+pub enum ExpressionKind {
     Binary {
         left: Box<Expression>,
         operator: Operator,
@@ -18,13 +28,156 @@ pub enum Expression {
         then: Box<Expression>,
         alternative: Box<Expression>,
     },
-    Identifier(String),
+    Identifier {
+        identifier: String,
+        resolution: Resolution,
+    },
     Assignment {
         identifier: String,
         expression: Box<Expression>,
+        resolution: Resolution,
     },
     FunctionInvokation {
         callee: Box<Expression>,
         arguments: Vec<Expression>,
     },
+    Get {
+        object: Box<Expression>,
+        property: String,
+    },
+    Set {
+        object: Box<Expression>,
+        property: String,
+        expression: Box<Expression>,
+    },
+    This {
+        resolution: Resolution,
+    },
+    Super {
+        method: String,
+        resolution: Resolution,
+    },
+    List(Vec<Expression>),
+    /// String with interpolated expressions, e.g. `"a = ${a}"`. Text parts are string literals.
+    Interpolation(Vec<Expression>),
+    /// Map literal, as key and value pairs.
+    Map(Vec<(Expression, Expression)>),
+    Index {
+        object: Box<Expression>,
+        index: Box<Expression>,
+    },
+    SetIndex {
+        object: Box<Expression>,
+        index: Box<Expression>,
+        expression: Box<Expression>,
+    },
+    /// `object[start:end]`, either bound may be left out.
+    Slice {
+        object: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+    },
+    /// Anonymous function, either `fun (a) { ... }` or `(a) => ...`.
+    Function {
+        parameters: Vec<String>,
+        execute: Box<Statement>,
+    },
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn identifier(identifier: String, span: Span) -> Self {
+        Self::new(
+            ExpressionKind::Identifier {
+                identifier,
+                resolution: Resolution::default(),
+            },
+            span,
+        )
+    }
+}
+
+/// Prints the expression as a parenthesized prefix tree, e.g. `(+ 1 (* 2 3))`.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExpressionKind::Binary {
+                left,
+                operator,
+                right,
+            } => write!(f, "({operator} {left} {right})"),
+            ExpressionKind::Unary { operator, right } => write!(f, "({operator} {right})"),
+            ExpressionKind::Literal(literal) => write!(f, "{literal}"),
+            ExpressionKind::Grouping(expr) => write!(f, "(group {expr})"),
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                alternative,
+            } => write!(f, "(?: {condition} {then} {alternative})"),
+            ExpressionKind::Identifier { identifier, .. } => write!(f, "{identifier}"),
+            ExpressionKind::Assignment {
+                identifier,
+                expression,
+                ..
+            } => write!(f, "(= {identifier} {expression})"),
+            ExpressionKind::FunctionInvokation { callee, arguments } => {
+                write!(f, "(call {callee}")?;
+                for argument in arguments {
+                    write!(f, " {argument}")?;
+                }
+                write!(f, ")")
+            }
+            ExpressionKind::Get { object, property } => write!(f, "(. {object} {property})"),
+            ExpressionKind::Set {
+                object,
+                property,
+                expression,
+            } => write!(f, "(= (. {object} {property}) {expression})"),
+            ExpressionKind::This { .. } => write!(f, "this"),
+            ExpressionKind::Super { method, .. } => write!(f, "(. super {method})"),
+            ExpressionKind::List(elements) => {
+                write!(f, "(list")?;
+                for element in elements {
+                    write!(f, " {element}")?;
+                }
+                write!(f, ")")
+            }
+            ExpressionKind::Interpolation(parts) => {
+                write!(f, "(str")?;
+                for part in parts {
+                    write!(f, " {part}")?;
+                }
+                write!(f, ")")
+            }
+            ExpressionKind::Map(entries) => {
+                write!(f, "(map")?;
+                for (key, value) in entries {
+                    write!(f, " ({key} {value})")?;
+                }
+                write!(f, ")")
+            }
+            ExpressionKind::Index { object, index } => write!(f, "([] {object} {index})"),
+            ExpressionKind::SetIndex {
+                object,
+                index,
+                expression,
+            } => write!(f, "(= ([] {object} {index}) {expression})"),
+            ExpressionKind::Slice { object, start, end } => {
+                write!(f, "([:] {object}")?;
+                for bound in [start, end] {
+                    match bound {
+                        Some(bound) => write!(f, " {bound}")?,
+                        None => write!(f, " _")?,
+                    }
+                }
+                write!(f, ")")
+            }
+            ExpressionKind::Function { parameters, .. } => {
+                write!(f, "(fun ({}))", parameters.join(" "))
+            }
+        }
+    }
 }
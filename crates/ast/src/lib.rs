@@ -1,4 +1,5 @@
 pub mod expression;
 pub mod literal;
 pub mod operator;
+pub mod resolution;
 pub mod statement;
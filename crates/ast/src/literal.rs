@@ -1,7 +1,22 @@
+use std::fmt;
+
 #[derive(Debug)]
 pub enum Literal {
     String(String),
+    Integer(i64),
     Number(f64),
     Boolean(bool),
     Nil,
 }
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::String(value) => write!(f, "{value:?}"),
+            Literal::Integer(value) => write!(f, "{value}"),
+            Literal::Number(value) => write!(f, "{value}"),
+            Literal::Boolean(value) => write!(f, "{value}"),
+            Literal::Nil => write!(f, "nil"),
+        }
+    }
+}
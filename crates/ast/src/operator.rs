@@ -1,3 +1,5 @@
+use std::fmt;
+
 use lexer::token::{token_type::TokenType, Token};
 
 #[derive(Debug, PartialEq)]
@@ -41,3 +43,28 @@ impl TryFrom<&Token> for Operator {
         }
     }
 }
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Operator::Equal => "==",
+                Operator::NotEqual => "!=",
+                Operator::Less => "<",
+                Operator::LessOrEqual => "<=",
+                Operator::Greater => ">",
+                Operator::GreaterOrEqual => ">=",
+                Operator::Addition => "+",
+                Operator::Subtraction => "-",
+                Operator::Multiplication => "*",
+                Operator::Division => "/",
+                Operator::Negation => "!",
+                Operator::Assignment => "=",
+                Operator::Conjunction => "and",
+                Operator::Disjunction => "or",
+            }
+        )
+    }
+}
@@ -0,0 +1,19 @@
+use std::cell::Cell;
+
+/// Location of a local variable as `(depth, slot)`, filled in by the resolver.
+///
+/// `depth` is the number of environments to walk up from the current one and
+/// `slot` is the index of the variable within that environment. An unresolved
+/// variable is looked up in the global environment by name.
+#[derive(Debug, Default)]
+pub struct Resolution(Cell<Option<(usize, usize)>>);
+
+impl Resolution {
+    pub fn resolve(&self, depth: usize, slot: usize) {
+        self.0.set(Some((depth, slot)));
+    }
+
+    pub fn get(&self) -> Option<(usize, usize)> {
+        self.0.get()
+    }
+}
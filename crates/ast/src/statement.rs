@@ -1,9 +1,17 @@
 use std::rc::Rc;
 
+use error::span::Span;
+
 use crate::expression::Expression;
 
 #[derive(Debug)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum StatementKind {
     Print(Expression),
     Expression(Expression),
     VariableDeclaration {
@@ -15,6 +23,11 @@ pub enum Statement {
         parameters: Vec<String>,
         execute: Box<Statement>,
     },
+    ClassDeclaration {
+        identifier: String,
+        superclass: Option<Expression>,
+        methods: Vec<Rc<Statement>>,
+    },
     Block(Vec<Rc<Statement>>),
     Conditional {
         condition: Expression,
@@ -25,7 +38,38 @@ pub enum Statement {
         condition: Expression,
         block: Box<Statement>,
     },
+    For {
+        initializer: Option<Box<Statement>>,
+        condition: Option<Expression>,
+        increment: Option<Expression>,
+        block: Box<Statement>,
+    },
     Break,
     Continue,
     Return(Expression),
+    Throw(Expression),
+    Try {
+        block: Box<Statement>,
+        /// Name bound to the caught value and the handler block.
+        catch: Option<(String, Box<Statement>)>,
+        finally: Option<Rc<Statement>>,
+    },
+    /// `import "path" as alias;`
+    Import {
+        path: String,
+        alias: String,
+    },
+    /// `from "path" import a, b;`
+    ImportFrom {
+        path: String,
+        names: Vec<String>,
+    },
+    /// A variable, function or class declaration visible to modules importing this one.
+    Export(Box<Statement>),
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Self { kind, span }
+    }
 }
@@ -0,0 +1,246 @@
+use std::fmt;
+
+use colored::Colorize;
+
+use crate::{span::Span, Error, InterpreterError};
+
+/// Secondary location attached to an error, e.g. a previous declaration.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// An error rendered together with the source lines it points at.
+///
+/// ```text
+/// compile-time error: Variable with identifier `a` is already defined in this scope.
+///  --> 1:14
+///   |
+/// 1 | { var a = 1; var a = 2; }
+///   |   ---------- variable first declared here
+///   |              ^^^^^^^^^^
+/// ```
+pub struct Diagnostic<'a, E: Error + ?Sized> {
+    error: &'a E,
+    source: &'a str,
+}
+
+struct Mark {
+    span: Span,
+    primary: bool,
+    message: Option<String>,
+}
+
+impl<'a, E: Error + ?Sized> Diagnostic<'a, E> {
+    /// Diagnostic for an error in `source`, or in the file the error names if it has one.
+    pub fn new(error: &'a E, source: &'a str) -> Self {
+        // without the text of its file, the error is shown without source lines
+        let source = match error.file() {
+            Some(_) => error.file_source().unwrap_or_default(),
+            None => source,
+        };
+
+        Self { error, source }
+    }
+
+    fn marks(&self) -> Vec<Mark> {
+        let mut marks = self
+            .error
+            .span()
+            .map(|span| Mark {
+                span,
+                primary: true,
+                message: None,
+            })
+            .into_iter()
+            .chain(self.error.labels().into_iter().map(|label| Mark {
+                span: label.span,
+                primary: false,
+                message: Some(label.message),
+            }))
+            .filter(|mark| self.line(mark.span.line).is_some())
+            .collect::<Vec<_>>();
+
+        marks.sort_by_key(|mark| (mark.span.line, mark.span.start));
+        marks
+    }
+
+    /// Byte offset and text of the 1-based `line`.
+    fn line(&self, line: usize) -> Option<(usize, &str)> {
+        let mut start = 0;
+
+        for (index, text) in self.source.split_inclusive('\n').enumerate() {
+            if index + 1 == line {
+                return Some((start, text.trim_end_matches(['\n', '\r'])));
+            }
+            start += text.len();
+        }
+
+        None
+    }
+
+    /// Whitespace leading up to the span and the underline below it, measured in characters
+    /// so that multi-byte text and tabs stay aligned.
+    fn underline(&self, span: Span, line_start: usize, text: &str) -> (String, usize) {
+        let line_end = line_start + text.len();
+
+        let indent = match self
+            .source
+            .get(line_start..span.start.clamp(line_start, line_end))
+        {
+            Some(prefix) => prefix
+                .chars()
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect(),
+            None => " ".repeat(span.column.saturating_sub(1)),
+        };
+
+        let length = self
+            .source
+            .get(span.start.clamp(line_start, line_end)..span.end.clamp(line_start, line_end))
+            .map(|underlined| underlined.chars().count())
+            .unwrap_or(0)
+            .max(1);
+
+        (indent, length)
+    }
+}
+
+impl<E: Error + ?Sized> fmt::Display for Diagnostic<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.error.kind().title().red(),
+            "error".red(),
+            self.error
+        )?;
+
+        let marks = self.marks();
+        let width = marks
+            .iter()
+            .map(|mark| mark.span.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gutter = " ".repeat(width);
+
+        if let Some(span) = self.error.span() {
+            let file = self
+                .error
+                .file()
+                .map(|file| format!("{}:", file.display()))
+                .unwrap_or_default();
+
+            write!(
+                f,
+                "\n{gutter}{} {file}{}:{}",
+                "-->".blue().bold(),
+                span.line,
+                span.column
+            )?;
+        }
+
+        if !marks.is_empty() {
+            write!(f, "\n{gutter} {}", "|".blue().bold())?;
+        }
+
+        let mut previous_line = None;
+
+        for mark in &marks {
+            let (line_start, text) = self.line(mark.span.line).unwrap();
+
+            if previous_line != Some(mark.span.line) {
+                write!(
+                    f,
+                    "\n{} {} {text}",
+                    format!("{:>width$}", mark.span.line).blue().bold(),
+                    "|".blue().bold()
+                )?;
+                previous_line = Some(mark.span.line);
+            }
+
+            let (indent, length) = self.underline(mark.span, line_start, text);
+            let underline = if mark.primary {
+                "^".repeat(length).red().bold()
+            } else {
+                "-".repeat(length).blue().bold()
+            };
+
+            write!(f, "\n{gutter} {} {indent}{underline}", "|".blue().bold())?;
+
+            if let Some(message) = &mark.message {
+                write!(f, " {}", message.blue().bold())?;
+            }
+        }
+
+        if let Some(help) = self.error.help() {
+            write!(f, "\n{gutter} {} {help}", "= help:".bold())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Every error produced while running one piece of source, along with that source.
+pub struct Diagnostics {
+    source: String,
+    errors: Vec<Box<dyn Error>>,
+}
+
+impl Diagnostics {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            errors: vec![],
+        }
+    }
+
+    pub fn push<E: Error + 'static>(&mut self, error: InterpreterError<E>) {
+        self.errors.push(Box::new(error.into_source()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Error> {
+        self.errors.iter().map(Box::as_ref)
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", Diagnostic::new(error.as_ref(), &self.source))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.errors.iter().map(|error| error.to_string()))
+            .finish()
+    }
+}
+
+impl std::error::Error for Diagnostics {}
@@ -1,4 +1,11 @@
+pub mod diagnostic;
+pub mod span;
+
+use std::path::Path;
+
 use colored::Colorize;
+use diagnostic::{Diagnostic, Label};
+use span::Span;
 
 #[derive(Debug, PartialEq)]
 pub enum ErrorKind {
@@ -6,13 +13,48 @@ pub enum ErrorKind {
     Runtime,
 }
 
+#[derive(Debug)]
 pub struct InterpreterError<E: Error> {
     source: E,
 }
 
 pub trait Error: std::error::Error {
-    fn line(&self) -> Option<usize>;
     fn kind(&self) -> ErrorKind;
+
+    /// Source text the error points at, if known.
+    fn span(&self) -> Option<Span> {
+        None
+    }
+    /// File the span points into, when it is not the source being run, e.g. an imported module.
+    fn file(&self) -> Option<&Path> {
+        None
+    }
+    /// Text of [`Error::file`] as it was compiled, which the span is shown against.
+    fn file_source(&self) -> Option<&str> {
+        None
+    }
+    fn line(&self) -> Option<usize> {
+        self.span().map(|span| span.line)
+    }
+    fn column(&self) -> Option<usize> {
+        self.span().map(|span| span.column)
+    }
+    /// Secondary locations related to the error.
+    fn labels(&self) -> Vec<Label> {
+        vec![]
+    }
+    fn help(&self) -> Option<String> {
+        None
+    }
+}
+
+impl ErrorKind {
+    fn title(&self) -> &'static str {
+        match self {
+            ErrorKind::Comptime => "compile-time",
+            ErrorKind::Runtime => "runtime",
+        }
+    }
 }
 
 impl<E> std::fmt::Display for InterpreterError<E>
@@ -23,18 +65,14 @@ where
         writeln!(
             f,
             "{} {}{}: {}",
-            if self.source.kind() == ErrorKind::Comptime {
-                "compile-time"
-            } else {
-                "runtime"
+            self.source.kind().title().red(),
+            "error".red(),
+            match (self.source.line(), self.source.column()) {
+                (Some(line), Some(column)) => format!("[{}:{line}:{column}]", self.file()),
+                (Some(line), None) => format!("[{}:{line}]", self.file()),
+                _ => String::new(),
             }
             .red(),
-            "error".red(),
-            self.source
-                .line()
-                .map(|line| format!("[:{line}]"))
-                .unwrap_or(String::new())
-                .red(),
             self.source
         )
     }
@@ -47,4 +85,28 @@ where
     pub fn new(source: E) -> Self {
         Self { source }
     }
+
+    fn file(&self) -> String {
+        self.source
+            .file()
+            .map(|file| file.display().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn source(&self) -> &E {
+        &self.source
+    }
+
+    pub fn source_mut(&mut self) -> &mut E {
+        &mut self.source
+    }
+
+    pub fn into_source(self) -> E {
+        self.source
+    }
+
+    /// Renders the error against the program text it was produced from.
+    pub fn diagnostic<'a>(&'a self, source: &'a str) -> Diagnostic<'a, E> {
+        Diagnostic::new(&self.source, source)
+    }
 }
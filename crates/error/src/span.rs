@@ -0,0 +1,30 @@
+/// Location of a piece of source text.
+///
+/// `start` and `end` are byte offsets into the source, `line` and `column`
+/// are the 1-based position of `start`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Span covering both `self` and everything up to the end of `other`.
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..*self
+        }
+    }
+}
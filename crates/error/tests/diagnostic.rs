@@ -0,0 +1,213 @@
+use std::fmt;
+
+use error::{
+    diagnostic::{Diagnostic, Label},
+    span::Span,
+    Error, ErrorKind,
+};
+
+#[derive(Debug, Default)]
+struct TestError {
+    span: Option<Span>,
+    labels: Vec<Label>,
+    help: Option<String>,
+}
+
+impl fmt::Display for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Something went wrong.")
+    }
+}
+
+impl std::error::Error for TestError {}
+
+impl Error for TestError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Comptime
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        self.labels.clone()
+    }
+
+    fn help(&self) -> Option<String> {
+        self.help.clone()
+    }
+}
+
+/// Renders `error` against `source` without colours, so it can be compared as plain text.
+fn render(error: &TestError, source: &str) -> String {
+    colored::control::set_override(false);
+
+    Diagnostic::new(error, source).to_string()
+}
+
+#[test]
+fn error_without_span_is_a_single_line() {
+    let error = TestError::default();
+
+    assert_eq!(
+        render(&error, "print 1;"),
+        "compile-time error: Something went wrong."
+    );
+}
+
+#[test]
+fn primary_span_is_underlined_below_its_line() {
+    let error = TestError {
+        span: Some(Span::new(17, 22, 2, 7)),
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, "var a = 1;\nprint a + b;\n"),
+        "\
+compile-time error: Something went wrong.
+ --> 2:7
+  |
+2 | print a + b;
+  |       ^^^^^"
+    );
+}
+
+#[test]
+fn empty_span_is_underlined_with_one_caret() {
+    let error = TestError {
+        span: Some(Span::new(7, 7, 1, 8)),
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, "print 1"),
+        "\
+compile-time error: Something went wrong.
+ --> 1:8
+  |
+1 | print 1
+  |        ^"
+    );
+}
+
+#[test]
+fn secondary_labels_are_ordered_by_position() {
+    let error = TestError {
+        span: Some(Span::new(13, 23, 1, 14)),
+        labels: vec![Label::new(
+            Span::new(2, 12, 1, 3),
+            "variable first declared here",
+        )],
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, "{ var a = 1; var a = 2; }"),
+        "\
+compile-time error: Something went wrong.
+ --> 1:14
+  |
+1 | { var a = 1; var a = 2; }
+  |   ---------- variable first declared here
+  |              ^^^^^^^^^^"
+    );
+}
+
+#[test]
+fn labels_on_other_lines_get_their_own_snippet() {
+    let source = "fun f() {}\n\n\n\n\n\n\n\n\nfun f() {}";
+    let error = TestError {
+        span: Some(Span::new(19, 29, 10, 1)),
+        labels: vec![Label::new(Span::new(0, 10, 1, 1), "first defined here")],
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, source),
+        "\
+compile-time error: Something went wrong.
+  --> 10:1
+   |
+ 1 | fun f() {}
+   | ---------- first defined here
+10 | fun f() {}
+   | ^^^^^^^^^^"
+    );
+}
+
+#[test]
+fn help_is_shown_after_the_snippet() {
+    let error = TestError {
+        span: Some(Span::new(0, 3, 1, 1)),
+        help: Some("declare it with `var` first".to_string()),
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, "abc = 1;"),
+        "\
+compile-time error: Something went wrong.
+ --> 1:1
+  |
+1 | abc = 1;
+  | ^^^
+  = help: declare it with `var` first"
+    );
+}
+
+#[test]
+fn multibyte_text_is_measured_in_characters() {
+    // "é" and "ü" take two bytes each
+    let source = "print \"héllo\" + wörld;";
+    let start = source.find("wörld").unwrap();
+    let error = TestError {
+        span: Some(Span::new(start, start + "wörld".len(), 1, 17)),
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, source),
+        "\
+compile-time error: Something went wrong.
+ --> 1:17
+  |
+1 | print \"héllo\" + wörld;
+  |                 ^^^^^"
+    );
+}
+
+#[test]
+fn tabs_are_kept_in_the_indent() {
+    let error = TestError {
+        span: Some(Span::new(2, 7, 1, 3)),
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, "\t\tprint;"),
+        "\
+compile-time error: Something went wrong.
+ --> 1:3
+  |
+1 | \t\tprint;
+  | \t\t^^^^^"
+    );
+}
+
+#[test]
+fn spans_past_the_last_line_have_no_snippet() {
+    let error = TestError {
+        span: Some(Span::new(40, 40, 5, 1)),
+        labels: vec![Label::new(Span::new(50, 51, 7, 1), "unreachable label")],
+        ..TestError::default()
+    };
+
+    assert_eq!(
+        render(&error, "print 1;\n"),
+        "\
+compile-time error: Something went wrong.
+--> 5:1"
+    );
+}
@@ -0,0 +1,179 @@
+use std::rc::Rc;
+
+use ::error::{diagnostic::Diagnostics, InterpreterError};
+use ast::statement::{Statement, StatementKind};
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{
+    error::{RuntimeError, RuntimeErrorKind},
+    runtime::{
+        convert::{FromRuntimeValue, IntoArguments, IntoRuntimeValue},
+        native::{Library, NativeFunction},
+        value::RuntimeValue,
+    },
+    Runtime,
+};
+
+pub type Value = Rc<RuntimeValue>;
+
+/// Runs Lox code inside a Rust host.
+///
+/// Globals persist between calls to [`Interpreter::eval`], so a host can load
+/// a script once and then call into it repeatedly. Declaring a global again
+/// replaces it, so a script can be reloaded after it changed.
+///
+/// ```
+/// use interpreter::Interpreter;
+///
+/// let interpreter = Interpreter::new();
+/// interpreter.set_global("limit", 10);
+/// interpreter
+///     .eval("fun allowed(amount) { return amount <= limit; }")
+///     .unwrap();
+///
+/// let allowed: bool = interpreter.call_as("allowed", (4,)).unwrap();
+/// assert!(allowed);
+/// ```
+#[derive(Debug)]
+pub struct Interpreter {
+    runtime: Runtime,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::with_runtime(Runtime::new())
+    }
+
+    /// Interpreter running on a preconfigured runtime, e.g. one built with
+    /// [`Runtime::with_io`] to capture script output.
+    pub fn with_runtime(runtime: Runtime) -> Self {
+        runtime.set_redefinable_globals(true);
+
+        Self { runtime }
+    }
+
+    /// Runs `source`, returning the value of its final statement if that is a
+    /// bare expression and `nil` otherwise. The final `;` may be left out.
+    /// Every evaluation gets a fresh step budget and timeout.
+    pub fn eval(&self, source: &str) -> Result<Value, Diagnostics> {
+        let trimmed = source.trim_end();
+        let source = if trimmed.is_empty() || trimmed.ends_with(';') || trimmed.ends_with('}') {
+            source.to_string()
+        } else {
+            format!("{trimmed};")
+        };
+        let source = source.as_str();
+
+        let program = self.compile(source)?;
+        self.runtime.reset_usage();
+
+        let mut result = Rc::new(RuntimeValue::nil());
+
+        for stmt in program {
+            let executed = match &stmt.kind {
+                StatementKind::Expression(expr) => self.runtime.evaluate(expr),
+                _ => self
+                    .runtime
+                    .run(&vec![stmt])
+                    .map(|_| Rc::new(RuntimeValue::nil())),
+            };
+
+            result = executed.map_err(|error| Self::diagnostics(source, error))?;
+        }
+
+        Ok(result)
+    }
+
+    pub fn get_global<T: FromRuntimeValue>(&self, identifier: &str) -> Option<T> {
+        self.runtime
+            .get_global(identifier)
+            .and_then(T::from_runtime_value)
+    }
+
+    pub fn set_global(&self, identifier: &str, value: impl IntoRuntimeValue) {
+        self.runtime
+            .set_global(identifier, Rc::new(value.into_runtime_value()));
+    }
+
+    pub fn define_native(&self, function: impl NativeFunction + 'static) {
+        self.runtime.define_native(function);
+    }
+
+    pub fn define_library(&self, library: Library) {
+        self.runtime.define_library(library);
+    }
+
+    /// Calls the global function or class `identifier` with a fresh step budget and timeout.
+    pub fn call(
+        &self,
+        identifier: &str,
+        arguments: impl IntoArguments,
+    ) -> Result<Value, Diagnostics> {
+        let callee = self.runtime.get_global(identifier).ok_or_else(|| {
+            Self::diagnostics(
+                "",
+                InterpreterError::new(RuntimeError::new(RuntimeErrorKind::VariableNotDefined(
+                    identifier.to_string(),
+                ))),
+            )
+        })?;
+
+        self.runtime.reset_usage();
+        self.runtime
+            .call(&callee, arguments.into_arguments())
+            .map_err(|error| Self::diagnostics("", error))
+    }
+
+    /// Like [`Interpreter::call`], converting the result. Returns `None` if the
+    /// call fails or the result has a different type.
+    pub fn call_as<T: FromRuntimeValue>(
+        &self,
+        identifier: &str,
+        arguments: impl IntoArguments,
+    ) -> Option<T> {
+        self.call(identifier, arguments)
+            .ok()
+            .and_then(T::from_runtime_value)
+    }
+
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    fn compile(&self, source: &str) -> Result<Vec<Rc<Statement>>, Diagnostics> {
+        let mut diagnostics = Diagnostics::new(source);
+
+        let scanner = Scanner::new(source.to_string());
+        if let Err(errors) = scanner.scan_tokens() {
+            errors.into_iter().for_each(|error| diagnostics.push(error));
+        }
+
+        let (program, errors) = Parser::new(scanner.tokens()).run();
+        errors.into_iter().for_each(|error| diagnostics.push(error));
+
+        if diagnostics.is_empty() {
+            if let Err(error) = Resolver::new().run(&program) {
+                diagnostics.push(error);
+            }
+        }
+
+        if diagnostics.is_empty() {
+            Ok(program)
+        } else {
+            Err(diagnostics)
+        }
+    }
+
+    fn diagnostics(source: &str, error: InterpreterError<RuntimeError>) -> Diagnostics {
+        let mut diagnostics = Diagnostics::new(source);
+        diagnostics.push(error);
+        diagnostics
+    }
+}
@@ -0,0 +1,139 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use interpreter::Interpreter;
+use runtime::{
+    error::RuntimeResult,
+    runtime::{
+        io::SharedBuffer,
+        native::{Builtin, Library},
+        value::RuntimeValue,
+    },
+    Runtime,
+};
+
+#[test]
+fn eval_returns_value_of_final_expression() {
+    let interpreter = Interpreter::new();
+
+    let value = interpreter.eval("var a = 20; a + 22").unwrap();
+    assert_eq!(value.to_string(), "42");
+
+    let value = interpreter.eval("var b = 1;").unwrap();
+    assert_eq!(value.to_string(), "nil");
+}
+
+#[test]
+fn globals_persist_and_can_be_redeclared() {
+    let interpreter = Interpreter::new();
+
+    interpreter
+        .eval("var count = 1; fun next() { return count + 1; }")
+        .unwrap();
+    interpreter.eval("var count = 10;").unwrap();
+    assert_eq!(interpreter.get_global::<i64>("count"), Some(10));
+    assert_eq!(interpreter.call_as::<i64>("next", ()), Some(11));
+
+    // reloading a script replaces its functions and classes
+    interpreter
+        .eval("fun next() { return count * 2; } class A {}")
+        .unwrap();
+    interpreter.eval("class A {}").unwrap();
+    assert_eq!(interpreter.call_as::<i64>("next", ()), Some(20));
+}
+
+#[test]
+fn local_redeclaration_is_still_rejected() {
+    let interpreter = Interpreter::new();
+
+    let errors = interpreter.eval("{ var a = 1; var a = 2; }").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(errors.to_string().contains("already defined in this scope"));
+}
+
+#[test]
+fn host_values_round_trip() {
+    let interpreter = Interpreter::new();
+
+    interpreter.set_global("names", vec!["a", "b"]);
+    interpreter.set_global(
+        "scores",
+        BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+    );
+    interpreter
+        .eval("var joined = names.join(\"+\"); var total = scores[\"a\"] + scores[\"b\"];")
+        .unwrap();
+
+    assert_eq!(
+        interpreter.get_global::<String>("joined").as_deref(),
+        Some("a+b")
+    );
+    assert_eq!(interpreter.get_global::<i64>("total"), Some(3));
+    assert_eq!(
+        interpreter.get_global::<Vec<String>>("names"),
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+    assert_eq!(interpreter.get_global::<bool>("total"), None);
+}
+
+#[test]
+fn call_reports_missing_function_and_runtime_errors() {
+    let interpreter = Interpreter::new();
+    interpreter
+        .eval("fun divide(a, b) { return a / b; }")
+        .unwrap();
+
+    assert_eq!(interpreter.call_as::<f64>("divide", (1, 4)), Some(0.25));
+
+    let errors = interpreter.call("divide", (1, 0)).unwrap_err();
+    assert!(errors.to_string().contains("Attempted to divide by zero."));
+
+    let errors = interpreter.call("missing", ()).unwrap_err();
+    assert!(errors.to_string().contains("`missing` is not defined"));
+}
+
+#[test]
+fn syntax_errors_are_all_reported() {
+    let interpreter = Interpreter::new();
+
+    let errors = interpreter.eval("var = 1;\nprint (;").unwrap_err();
+    assert_eq!(errors.len(), 2);
+}
+
+fn double(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let RuntimeValue::Integer(value) = arguments[0].as_ref() else {
+        unreachable!()
+    };
+
+    Ok(Rc::new(RuntimeValue::integer(value * 2)))
+}
+
+#[test]
+fn natives_and_libraries_are_callable() {
+    let interpreter = Interpreter::new();
+
+    interpreter.define_native(Builtin::new("double", 1, double));
+    interpreter.define_library(Library::new(
+        "host",
+        vec![
+            (
+                "twice".to_string(),
+                RuntimeValue::native(Builtin::new("twice", 1, double)),
+            ),
+            ("version".to_string(), RuntimeValue::integer(3)),
+        ],
+    ));
+
+    let value = interpreter
+        .eval("double(4) + host.twice(host.version)")
+        .unwrap();
+    assert_eq!(value.to_string(), "14");
+}
+
+#[test]
+fn output_goes_to_the_runtime_sink() {
+    let output = SharedBuffer::new();
+    let interpreter = Interpreter::with_runtime(Runtime::with_output(output.clone()));
+
+    interpreter.eval("print \"hello\"; print 1 + 1;").unwrap();
+    assert_eq!(output.contents(), "hello\n2\n");
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use error::InterpreterError;
+use error::{span::Span, InterpreterError};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,21 +13,41 @@ pub enum ScannerErrorKind {
 
     #[error("Unterminated string.")]
     UnterminatedString,
+
+    #[error("Invalid escape sequence `{0}`.")]
+    InvalidEscape(String),
 }
 
 #[derive(Debug)]
 pub struct ScannerError {
     kind: ScannerErrorKind,
-    line: usize,
+    span: Span,
 }
 
 impl error::Error for ScannerError {
-    fn line(&self) -> Option<usize> {
-        Some(self.line)
+    fn span(&self) -> Option<Span> {
+        Some(self.span)
     }
+
     fn kind(&self) -> error::ErrorKind {
         error::ErrorKind::Comptime
     }
+
+    fn help(&self) -> Option<String> {
+        match self.kind {
+            ScannerErrorKind::UnterminatedBlockComment => {
+                Some("close the comment with `*/`".to_string())
+            }
+            ScannerErrorKind::UnterminatedString => {
+                Some("add a closing `\"` to the string".to_string())
+            }
+            ScannerErrorKind::InvalidEscape(_) => Some(
+                "supported escapes are `\\n`, `\\t`, `\\\"`, `\\\\`, `\\$` and `\\u{...}`"
+                    .to_string(),
+            ),
+            ScannerErrorKind::UnexpectedCharacter(_) => None,
+        }
+    }
 }
 
 impl std::error::Error for ScannerError {}
@@ -38,8 +58,8 @@ impl fmt::Display for ScannerError {
 }
 
 impl ScannerError {
-    pub fn new(kind: ScannerErrorKind, line: usize) -> Self {
-        Self { kind, line }
+    pub fn new(kind: ScannerErrorKind, span: Span) -> Self {
+        Self { kind, span }
     }
 }
 
@@ -1,3 +1,5 @@
 pub mod error;
 pub mod scanner;
 pub mod token;
+
+pub use ::error::span::Span;
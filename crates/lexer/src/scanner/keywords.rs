@@ -23,6 +23,14 @@ pub static KEYWORDS: LazyLock<HashMap<String, TokenType>> = LazyLock::new(|| {
     map.insert("var".to_string(), TokenType::Var);
     map.insert("break".to_string(), TokenType::Break);
     map.insert("continue".to_string(), TokenType::Continue);
+    map.insert("throw".to_string(), TokenType::Throw);
+    map.insert("try".to_string(), TokenType::Try);
+    map.insert("catch".to_string(), TokenType::Catch);
+    map.insert("finally".to_string(), TokenType::Finally);
+    map.insert("import".to_string(), TokenType::Import);
+    map.insert("export".to_string(), TokenType::Export);
+    map.insert("from".to_string(), TokenType::From);
+    map.insert("as".to_string(), TokenType::As);
 
     map
 });
@@ -2,7 +2,7 @@ pub mod keywords;
 
 use std::cell::RefCell;
 
-use error::InterpreterError;
+use error::{span::Span, InterpreterError};
 use keywords::KEYWORDS;
 
 use crate::{
@@ -15,6 +15,12 @@ pub struct Scanner {
     start: RefCell<usize>,
     current: RefCell<usize>,
     line: RefCell<usize>,
+    /// Column of the current position, counted in characters.
+    column: RefCell<usize>,
+    /// Line and column at which the token being scanned begins.
+    start_position: RefCell<(usize, usize)>,
+    /// Depth of braces opened inside each `${ ... }` being scanned, innermost last.
+    interpolations: RefCell<Vec<usize>>,
     tokens: RefCell<Vec<Token>>,
 }
 
@@ -25,21 +31,40 @@ impl Scanner {
             start: RefCell::new(0),
             current: RefCell::new(0),
             line: RefCell::new(1),
+            column: RefCell::new(1),
+            start_position: RefCell::new((1, 1)),
+            interpolations: RefCell::new(vec![]),
             tokens: RefCell::new(vec![]),
         }
     }
 
-    pub fn scan_tokens(&self) -> ScannerResult<()> {
+    /// Scans the whole source. Malformed input is reported but skipped, so the
+    /// tokens around it are still produced and every error is returned at once.
+    pub fn scan_tokens(&self) -> Result<(), Vec<InterpreterError<ScannerError>>> {
+        let mut errors = vec![];
+
         while !self.is_at_end() {
             *self.start.borrow_mut() = self.current();
-            self.scan_token()?;
+            *self.start_position.borrow_mut() = (self.line(), self.column());
+
+            if let Err(error) = self.scan_token() {
+                errors.push(error);
+            }
         }
 
-        self.tokens
-            .borrow_mut()
-            .push(Token::new(TokenType::EOF, String::new(), self.line(), None));
+        let end = self.source.len();
+        self.tokens.borrow_mut().push(Token::new(
+            TokenType::EOF,
+            String::new(),
+            None,
+            Span::new(end, end, self.line(), self.column()),
+        ));
 
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 
     fn scan_token(&self) -> ScannerResult<()> {
@@ -48,8 +73,29 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(depth) = self.interpolations.borrow_mut().last_mut() {
+                    *depth += 1;
+                }
+
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => {
+                let closes_interpolation = self.interpolations.borrow().last() == Some(&0);
+
+                if closes_interpolation {
+                    self.interpolations.borrow_mut().pop();
+                    self.string()?;
+                } else {
+                    if let Some(depth) = self.interpolations.borrow_mut().last_mut() {
+                        *depth -= 1;
+                    }
+
+                    self.add_token(TokenType::RightBrace)
+                }
+            }
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
             '.' => self.add_token(TokenType::Dot),
             '-' => self.add_token(TokenType::Minus),
@@ -65,6 +111,8 @@ impl Scanner {
             }),
             '=' => self.add_token(if self.match_char('=') {
                 TokenType::EqualEqual
+            } else if self.match_char('>') {
+                TokenType::Arrow
             } else {
                 TokenType::Equal
             }),
@@ -86,10 +134,6 @@ impl Scanner {
                     }
                 } else if self.match_char('*') {
                     while self.peek() != '*' && self.peek_next() != '/' && !self.is_at_end() {
-                        if self.peek() == '\n' {
-                            self.advance_lines();
-                        }
-
                         self.advance();
                     }
 
@@ -105,13 +149,12 @@ impl Scanner {
                     self.add_token(TokenType::Slash);
                 }
             }
-            ' ' | '\r' | '\t' => {}
-            '\n' => self.advance_lines(),
+            ' ' | '\r' | '\t' | '\n' => {}
             '"' => self.string()?,
             other => {
                 if other.is_ascii_digit() {
                     self.number();
-                } else if other.is_ascii_alphabetic() {
+                } else if other.is_alphabetic() {
                     self.identifier();
                 } else {
                     return Err(self.construct_error(ScannerErrorKind::UnexpectedCharacter(other)));
@@ -123,7 +166,7 @@ impl Scanner {
     }
 
     fn identifier(&self) {
-        while self.peek().is_ascii_alphanumeric() {
+        while self.peek().is_alphanumeric() {
             self.advance();
         }
 
@@ -149,16 +192,42 @@ impl Scanner {
             }
         }
 
-        let value = self.source[self.start()..self.current()].parse().unwrap();
-        self.add_literal_token(TokenType::Number, Some(TokenLiteral::Number(value)));
+        // integers are kept exact, unless they are too large to be one
+        let text = &self.source[self.start()..self.current()];
+        let literal = match text.parse() {
+            Ok(value) => TokenLiteral::Integer(value),
+            Err(_) => TokenLiteral::Number(text.parse().unwrap()),
+        };
+        self.add_literal_token(TokenType::Number, Some(literal));
     }
 
+    /// Scans string text once its opening `"`, or the `}` closing an interpolation, has been
+    /// consumed. Text followed by `${` is added as an interpolation token instead.
     fn string(&self) -> ScannerResult<()> {
+        let mut value = String::new();
+        // the rest of the string is still scanned after a bad escape so it is not mistaken for code
+        let mut invalid_escape = None;
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.advance_lines();
+            match self.advance() {
+                '\\' => match self.escape() {
+                    Ok(c) => value.push(c),
+                    Err(error) => {
+                        invalid_escape.get_or_insert(error);
+                    }
+                },
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.interpolations.borrow_mut().push(0);
+                    self.add_literal_token(
+                        TokenType::Interpolation,
+                        Some(TokenLiteral::String(value)),
+                    );
+
+                    return invalid_escape.map_or(Ok(()), Err);
+                }
+                c => value.push(c),
             }
-            self.advance();
         }
 
         if self.is_at_end() {
@@ -168,50 +237,89 @@ impl Scanner {
         // Consume closing '"'
         self.advance();
 
-        let value = self.source[(self.start() + 1)..(self.current() - 1)].to_string();
+        // the token is kept either way, so the parser does not report the string as missing
         self.add_literal_token(TokenType::String, Some(TokenLiteral::String(value)));
 
-        Ok(())
+        invalid_escape.map_or(Ok(()), Err)
     }
 
-    fn peek_next(&self) -> char {
-        if self.current() + 1 >= self.source.len() {
-            '\0'
-        } else {
-            self.source_index(self.current() + 1)
+    /// Decodes the escape sequence following a `\` inside a string.
+    fn escape(&self) -> ScannerResult<char> {
+        // the backslash has already been consumed
+        let start = self.current() - 1;
+        let (line, column) = (self.line(), self.column() - 1);
+
+        let escaped = match self.peek() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            '"' => Some('"'),
+            '\\' => Some('\\'),
+            '$' => Some('$'),
+            'u' if self.peek_next() == '{' => {
+                self.advance_by(2);
+
+                let digits = self.current();
+                while self.peek().is_ascii_hexdigit() {
+                    self.advance();
+                }
+                let code = &self.source[digits..self.current()];
+
+                (code.len() <= 6 && self.peek() == '}')
+                    .then(|| u32::from_str_radix(code, 16).ok().and_then(char::from_u32))
+                    .flatten()
+            }
+            _ => None,
+        };
+
+        // consume the last character of the sequence, unless the string is cut short
+        if !self.is_at_end() && (escaped.is_some() || self.peek() != '"') {
+            self.advance();
         }
+
+        escaped.ok_or_else(|| {
+            let sequence = self.source[start..self.current()].to_string();
+
+            InterpreterError::new(ScannerError::new(
+                ScannerErrorKind::InvalidEscape(sequence),
+                Span::new(start, self.current(), line, column),
+            ))
+        })
     }
 
-    fn peek(&self) -> char {
-        if self.is_at_end() {
-            '\0'
-        } else {
-            self.source_index(self.current())
-        }
+    fn peek_next(&self) -> char {
+        self.remaining().nth(1).unwrap_or('\0')
     }
 
-    fn advance_lines(&self) {
-        *self.line.borrow_mut() += 1;
+    fn peek(&self) -> char {
+        self.remaining().next().unwrap_or('\0')
     }
 
     fn match_char(&self, expected: char) -> bool {
-        if self.is_at_end() || self.source_index(self.current()) != expected {
+        if self.is_at_end() || self.peek() != expected {
             false
         } else {
-            *self.current.borrow_mut() += 1;
+            self.advance_by(expected.len_utf8());
             true
         }
     }
 
     fn advance(&self) -> char {
-        let char = self.source.as_bytes()[self.current()] as char;
+        let char = self.peek();
+
+        self.advance_by(char.len_utf8());
 
-        self.advance_by(1);
+        if char == '\n' {
+            *self.line.borrow_mut() += 1;
+            *self.column.borrow_mut() = 1;
+        }
 
         char
     }
 
     fn advance_by(&self, advance: usize) {
+        let current = self.current();
+
+        *self.column.borrow_mut() += self.source[current..current + advance].chars().count();
         *self.current.borrow_mut() += advance;
     }
 
@@ -224,15 +332,16 @@ impl Scanner {
 
         self.tokens
             .borrow_mut()
-            .push(Token::new(token_type, lexeme, self.line(), literal))
+            .push(Token::new(token_type, lexeme, literal, self.span()))
     }
 
     fn is_at_end(&self) -> bool {
         self.current() >= self.source.len()
     }
 
-    fn source_index(&self, idx: usize) -> char {
-        self.source.as_bytes()[idx] as char
+    /// Characters from the current position onwards.
+    fn remaining(&self) -> std::str::Chars<'_> {
+        self.source[self.current()..].chars()
     }
 
     fn start(&self) -> usize {
@@ -246,6 +355,17 @@ impl Scanner {
     fn line(&self) -> usize {
         *self.line.borrow()
     }
+
+    fn column(&self) -> usize {
+        *self.column.borrow()
+    }
+
+    /// Span of the token scanned so far.
+    fn span(&self) -> Span {
+        let (line, column) = *self.start_position.borrow();
+
+        Span::new(self.start(), self.current(), line, column)
+    }
 }
 
 impl Scanner {
@@ -254,6 +374,6 @@ impl Scanner {
     }
 
     fn construct_error(&self, kind: ScannerErrorKind) -> InterpreterError<ScannerError> {
-        InterpreterError::new(ScannerError::new(kind, self.line()))
+        InterpreterError::new(ScannerError::new(kind, self.span()))
     }
 }
@@ -1,6 +1,8 @@
 use token_literal::TokenLiteral;
 use token_type::TokenType;
 
+use error::span::Span;
+
 pub mod token_literal;
 pub mod token_type;
 
@@ -8,22 +10,22 @@ pub mod token_type;
 pub struct Token {
     token_type: TokenType,
     lexeme: String,
-    line: usize,
     literal: Option<TokenLiteral>,
+    span: Span,
 }
 
 impl Token {
     pub fn new(
         token_type: TokenType,
         lexeme: String,
-        line: usize,
         literal: Option<TokenLiteral>,
+        span: Span,
     ) -> Self {
         Self {
             lexeme,
-            line,
             token_type,
             literal,
+            span,
         }
     }
 
@@ -36,7 +38,11 @@ impl Token {
     }
 
     pub fn line(&self) -> usize {
-        self.line
+        self.span.line
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
     }
 
     pub fn literal(&self) -> Option<&TokenLiteral> {
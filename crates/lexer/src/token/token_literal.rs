@@ -1,6 +1,7 @@
 #[derive(Debug, PartialEq)]
 pub enum TokenLiteral {
     String(String),
+    Integer(i64),
     Number(f64),
 }
 
@@ -12,6 +13,13 @@ impl TokenLiteral {
         }
     }
 
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(num) => Some(*num),
+            _ => None,
+        }
+    }
+
     pub fn as_number(&self) -> Option<f64> {
         match self {
             Self::Number(num) => Some(*num),
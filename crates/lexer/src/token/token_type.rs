@@ -5,6 +5,8 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -24,10 +26,13 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    Arrow,
 
     // Literals.
     Identifier,
     String,
+    /// Text of a string up to an interpolated `${`. The string continues after the matching `}`.
+    Interpolation,
     Number,
 
     // Keywords.
@@ -49,6 +54,14 @@ pub enum TokenType {
     While,
     Break,
     Continue,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Import,
+    Export,
+    From,
+    As,
 
     EOF,
 }
@@ -1,12 +1,34 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "Lox")]
 #[command(version, about = "A Rust-based Lox compiler.")]
 pub struct Args {
-    pub file: Vec<PathBuf>,
+    pub file: Option<PathBuf>,
+
+    /// Arguments handed to the script, which it reads with `args()`.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub arguments: Vec<String>,
+
+    /// Execution engine used to run the program. Memory limits are only enforced by
+    /// the tree-walker; the VM refuses to run when one is set.
+    #[arg(long, value_enum, default_value_t = Backend::Tree)]
+    pub backend: Backend,
+
+    /// Directory searched for imported modules not found next to the importing file.
+    #[arg(long, value_name = "DIR")]
+    pub module_path: Vec<PathBuf>,
+
+    /// Lets the script read and write files, and import modules outside its own
+    /// directory and the module path.
+    #[arg(long)]
+    pub allow_fs: bool,
+
+    /// Lets the script read environment variables and its arguments.
+    #[arg(long)]
+    pub allow_env: bool,
 
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -16,3 +38,12 @@ pub struct Args {
 pub enum Commands {
     Eval { code: String },
 }
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Backend {
+    /// Tree-walking interpreter.
+    Tree,
+    /// Bytecode compiler and virtual machine. Does not track memory, so it cannot run
+    /// under a memory limit.
+    Vm,
+}
@@ -1,7 +1,19 @@
+use std::io;
+
+use rustyline::error::ReadlineError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum CliError {
-    #[error("No file name provided.")]
-    MissingFilename,
+    #[error("Could not read `{0}`: {1}.")]
+    FileNotReadable(String, io::Error),
+
+    #[error("Unknown command `:{0}`, see `:help`.")]
+    UnknownCommand(String),
+
+    #[error("Expected a single expression.")]
+    ExpressionExpected,
+
+    #[error("Line editor failure: {0}.")]
+    Editor(#[from] ReadlineError),
 }
@@ -1,48 +1,103 @@
 pub mod args;
 pub mod error;
+pub mod repl;
 
 use std::{fs, process};
 
-use args::Args;
+use args::{Args, Backend};
 use clap::Parser as _;
 use colored::Colorize;
-use error::CliError;
 use lexer::scanner::Scanner;
 use parser::Parser;
-use runtime::Runtime;
+use repl::Repl;
+use resolver::Resolver;
+use runtime::{runtime::capabilities::Capabilities, Runtime};
+use vm::{compiler::Compiler, Vm};
 
 fn main() {
     let args = Args::parse();
 
+    // reading input and exiting are always fine for a script run by hand
+    let capabilities = Capabilities {
+        fs: args.allow_fs,
+        env: args.allow_env,
+        ..Capabilities::all()
+    };
+
     let input = args
         .file
-        .get(0)
+        .as_ref()
         .map(|path| fs::read_to_string(path).unwrap())
-        .unwrap_or_else(|| {
-            match args.command.unwrap_or_else(|| {
-                eprintln!("{}", CliError::MissingFilename.to_string().red());
-                process::exit(1);
-            }) {
+        .or_else(|| {
+            args.command.map(|command| match command {
                 args::Commands::Eval { code } => code,
-            }
+            })
         });
 
-    let scanner = Scanner::new(input);
-    scanner.scan_tokens().unwrap_or_else(|err| {
-        eprintln!("{err}");
-        process::exit(1)
-    });
+    let Some(input) = input else {
+        Repl::with_capabilities(capabilities)
+            .run()
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err.to_string().red());
+                process::exit(1);
+            });
+
+        return;
+    };
+
+    let scanner = Scanner::new(input.clone());
+    let scanner_errors = scanner.scan_tokens().err().unwrap_or_default();
 
     let tokens = scanner.tokens();
 
+    // syntax errors from both stages are reported together
     let parser = Parser::new(tokens);
-    let tree = parser.run().unwrap_or_else(|err| {
-        eprintln!("{err}");
+    let (tree, parser_errors) = parser.run();
+
+    for err in &scanner_errors {
+        eprintln!("{}", err.diagnostic(&input));
+    }
+    for err in &parser_errors {
+        eprintln!("{}", err.diagnostic(&input));
+    }
+    if !scanner_errors.is_empty() || !parser_errors.is_empty() {
+        process::exit(1);
+    }
+
+    Resolver::new().run(&tree).unwrap_or_else(|err| {
+        eprintln!("{}", err.diagnostic(&input));
         process::exit(1)
     });
 
-    Runtime::new().run(&tree).unwrap_or_else(|err| {
-        eprintln!("{err}");
+    let runtime = Runtime::new();
+    runtime.set_capabilities(capabilities);
+    runtime.set_arguments(args.arguments);
+
+    if let Some(path) = &args.file {
+        runtime.modules().set_main(path);
+    }
+    for directory in &args.module_path {
+        runtime.modules().add_search_path(directory);
+    }
+
+    let result = match args.backend {
+        Backend::Tree => runtime.run(&tree),
+        Backend::Vm => {
+            let function = Compiler::new().compile(&tree).unwrap_or_else(|err| {
+                eprintln!("{}", err.diagnostic(&input));
+                process::exit(1)
+            });
+
+            Vm::with_host(runtime).run(function)
+        }
+    };
+
+    if let Err(err) = result {
+        if let Some(code) = err.source().exit_code() {
+            process::exit(code);
+        }
+
+        eprintln!("{}", err.diagnostic(&input));
         process::exit(1);
-    });
+    }
 }
@@ -0,0 +1,234 @@
+use std::{env, fs, path::PathBuf, process, rc::Rc};
+
+use ast::statement::{Statement, StatementKind};
+use colored::Colorize;
+use lexer::{scanner::Scanner, token::token_type::TokenType};
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{
+    runtime::{capabilities::Capabilities, value::RuntimeValue},
+    Runtime,
+};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+use crate::error::CliError;
+
+const HISTORY_FILE: &str = ".lox_history";
+
+const HELP: &str = "\
+Enter Lox statements or expressions; the values of bare expressions are printed.
+Input continues on the next line while braces or parentheses are left open.
+
+  :help          Show this message
+  :reset         Discard all global definitions
+  :load <file>   Run a file in the current session
+  :ast <expr>    Print the syntax tree of an expression
+";
+
+/// Interactive session keeping a single runtime alive between inputs.
+pub struct Repl {
+    runtime: Runtime,
+    capabilities: Capabilities,
+    history: Option<PathBuf>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self::with_capabilities(Capabilities::default())
+    }
+
+    /// Session whose runtime grants `capabilities`, also after a `:reset`.
+    pub fn with_capabilities(capabilities: Capabilities) -> Self {
+        Self {
+            runtime: Self::runtime(capabilities),
+            capabilities,
+            history: env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE)),
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), CliError> {
+        let mut editor = DefaultEditor::new()?;
+
+        if let Some(history) = &self.history {
+            // a missing history file just means this is the first session
+            let _ = editor.load_history(history);
+        }
+
+        let mut buffer = String::new();
+
+        loop {
+            let prompt = if buffer.is_empty() { "> " } else { "... " };
+
+            match editor.readline(prompt) {
+                Ok(line) => {
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+
+                    if is_incomplete(&buffer) {
+                        continue;
+                    }
+
+                    let input = std::mem::take(&mut buffer);
+                    let input = input.trim();
+
+                    if input.is_empty() {
+                        continue;
+                    }
+
+                    editor.add_history_entry(input)?;
+
+                    match input.strip_prefix(':') {
+                        Some(command) => self.command(command),
+                        None => self.execute(&terminated(input), true),
+                    }
+                }
+                Err(ReadlineError::Interrupted) => buffer.clear(),
+                Err(ReadlineError::Eof) => break,
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        if let Some(history) = &self.history {
+            editor.save_history(history)?;
+        }
+
+        Ok(())
+    }
+
+    fn command(&mut self, command: &str) {
+        let (name, argument) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, argument)| (name, argument.trim()))
+            .unwrap_or((command, ""));
+
+        match name {
+            "help" => print!("{HELP}"),
+            "reset" => self.runtime = Self::runtime(self.capabilities),
+            "load" => match fs::read_to_string(argument) {
+                Ok(source) => {
+                    // imports in the file are relative to it, not to the working directory
+                    self.runtime.modules().set_main(argument);
+                    self.execute(&source, false);
+                    self.runtime.modules().clear_main();
+                }
+                Err(error) => report(CliError::FileNotReadable(argument.to_string(), error)),
+            },
+            "ast" => ast(&terminated(argument)),
+            _ => report(CliError::UnknownCommand(name.to_string())),
+        }
+    }
+
+    /// Runs `source` against the session runtime. With `echo` set, the values of
+    /// top-level expression statements are printed.
+    fn execute(&self, source: &str, echo: bool) {
+        let Some(program) = parse(source) else {
+            return;
+        };
+
+        if let Err(err) = Resolver::new().run(&program) {
+            eprintln!("{}", err.diagnostic(source));
+            return;
+        }
+
+        for stmt in program {
+            let result = match &stmt.kind {
+                StatementKind::Expression(expr) if echo => {
+                    self.runtime.evaluate(expr).map(|value| {
+                        if !matches!(value.as_ref(), RuntimeValue::Nil) {
+                            println!("{value}");
+                        }
+                    })
+                }
+                _ => self.runtime.run(&vec![stmt]),
+            };
+
+            if let Err(err) = result {
+                if let Some(code) = err.source().exit_code() {
+                    process::exit(code);
+                }
+
+                eprintln!("{}", err.diagnostic(source));
+                return;
+            }
+        }
+    }
+
+    fn runtime(capabilities: Capabilities) -> Runtime {
+        let runtime = Runtime::new();
+        runtime.set_capabilities(capabilities);
+        runtime.set_redefinable_globals(true);
+        runtime
+    }
+}
+
+fn ast(source: &str) {
+    let Some(program) = parse(source) else {
+        return;
+    };
+
+    match program.as_slice() {
+        [stmt] => match &stmt.kind {
+            StatementKind::Expression(expr) => println!("{expr}"),
+            _ => report(CliError::ExpressionExpected),
+        },
+        _ => report(CliError::ExpressionExpected),
+    }
+}
+
+/// Scans and parses `source`, reporting every syntax error found.
+fn parse(source: &str) -> Option<Vec<Rc<Statement>>> {
+    let scanner = Scanner::new(source.to_string());
+    let scanner_errors = scanner.scan_tokens().err().unwrap_or_default();
+
+    let (program, parser_errors) = Parser::new(scanner.tokens()).run();
+
+    for err in &scanner_errors {
+        eprintln!("{}", err.diagnostic(source));
+    }
+    for err in &parser_errors {
+        eprintln!("{}", err.diagnostic(source));
+    }
+
+    (scanner_errors.is_empty() && parser_errors.is_empty()).then_some(program)
+}
+
+/// Whether `source` still has unclosed braces, brackets, parentheses or interpolations.
+fn is_incomplete(source: &str) -> bool {
+    let scanner = Scanner::new(source.to_string());
+    let _ = scanner.scan_tokens();
+
+    let depth = scanner
+        .tokens()
+        .iter()
+        .fold(0, |depth, token| match token.token_type() {
+            TokenType::LeftBrace | TokenType::LeftParen | TokenType::LeftBracket => depth + 1,
+            TokenType::RightBrace | TokenType::RightParen | TokenType::RightBracket => depth - 1,
+            // string text opening or closing an interpolated `${ ... }`
+            TokenType::Interpolation if token.lexeme().starts_with('"') => depth + 1,
+            TokenType::String if token.lexeme().starts_with('}') => depth - 1,
+            _ => depth,
+        });
+
+    depth > 0
+}
+
+/// Lets a single expression or statement be entered without its trailing `;`.
+fn terminated(input: &str) -> String {
+    let input = input.trim_end();
+
+    if input.ends_with(';') || input.ends_with('}') {
+        input.to_string()
+    } else {
+        format!("{input};")
+    }
+}
+
+fn report(error: CliError) {
+    eprintln!("{}", error.to_string().red());
+}
@@ -0,0 +1,124 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+struct Output {
+    stdout: String,
+    stderr: String,
+    code: Option<i32>,
+}
+
+fn run(backend: &str, script: &Path) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_lox"))
+        .env("NO_COLOR", "1")
+        .args(["--backend", backend])
+        .arg(script)
+        .output()
+        .unwrap();
+
+    Output {
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+        code: output.status.code(),
+    }
+}
+
+/// Lox scripts directly inside `directory`, in a stable order.
+fn scripts(directory: &Path) -> Vec<PathBuf> {
+    let mut scripts = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        // directories hold the modules imported by the scripts
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+        .collect::<Vec<_>>();
+
+    scripts.sort();
+    scripts
+}
+
+fn manifest_path(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+/// Every example prints the output recorded next to the tests, on both backends.
+#[test]
+fn examples_print_expected_output() {
+    for script in scripts(&manifest_path("../../examples")) {
+        let name = script.file_stem().unwrap().to_string_lossy();
+        let expected = fs::read_to_string(manifest_path(&format!("tests/expected/{name}.out")))
+            .unwrap_or_else(|_| panic!("no expected output for {}", script.display()));
+
+        let tree = run("tree", &script);
+        let vm = run("vm", &script);
+
+        assert_eq!(tree.stdout, expected, "tree output of {}", script.display());
+        assert_eq!(vm.stdout, expected, "vm output of {}", script.display());
+        assert_eq!(tree.stderr, vm.stderr, "errors of {}", script.display());
+        assert_eq!(tree.code, vm.code, "exit code of {}", script.display());
+    }
+}
+
+/// What a test program states it prints, through `// expect: <line>` comments,
+/// and the runtime error it ends with, through an `// expect error: <message>`
+/// comment on the line the error points at.
+struct Expectation {
+    stdout: String,
+    error: Option<(usize, String)>,
+}
+
+impl Expectation {
+    fn parse(source: &str) -> Self {
+        let mut stdout = String::new();
+        let mut error = None;
+
+        for (index, line) in source.lines().enumerate() {
+            if let Some((_, expected)) = line.split_once("// expect: ") {
+                stdout.push_str(expected);
+                stdout.push('\n');
+            } else if let Some((_, message)) = line.split_once("// expect error: ") {
+                error = Some((index + 1, message.to_string()));
+            }
+        }
+
+        Self { stdout, error }
+    }
+
+    fn check(&self, backend: &str, script: &Path) {
+        let output = run(backend, script);
+        let script = script.display();
+
+        assert_eq!(
+            output.stdout, self.stdout,
+            "{backend} output of {script}, with errors:\n{}",
+            output.stderr
+        );
+
+        match &self.error {
+            Some((line, message)) => {
+                assert_eq!(output.code, Some(1), "{backend} exit code of {script}");
+                assert!(
+                    output.stderr.contains(message)
+                        && output.stderr.contains(&format!("--> {line}:")),
+                    "{backend} should report `{message}` on line {line} of {script}, reported:\n{}",
+                    output.stderr
+                );
+            }
+            None => {
+                assert_eq!(output.stderr, "", "{backend} errors of {script}");
+                assert_eq!(output.code, Some(0), "{backend} exit code of {script}");
+            }
+        }
+    }
+}
+
+#[test]
+fn programs_behave_as_expected() {
+    for script in scripts(&manifest_path("tests/programs")) {
+        let expectation = Expectation::parse(&fs::read_to_string(&script).unwrap());
+
+        expectation.check("tree", &script);
+        expectation.check("vm", &script);
+    }
+}
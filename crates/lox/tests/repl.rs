@@ -0,0 +1,55 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Output and errors of a session fed `input` line by line.
+fn session(input: &str) -> (String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lox"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("NO_COLOR", "1")
+        .env_remove("HOME")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn globals_can_be_redefined() {
+    let (stdout, stderr) = session(
+        "var x = 1;\nvar x = x + 1;\nx\nfun f() { return 1; }\nfun f() { return 2; }\nf()\nclass A {}\nclass A {}\n",
+    );
+
+    assert_eq!(stderr, "");
+    assert_eq!(stdout, "2\n2\n");
+}
+
+#[test]
+fn loaded_files_import_relative_to_themselves() {
+    let (stdout, stderr) = session(":load ../../examples/modules.lox\n");
+
+    let expected = std::fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/expected/modules.out"),
+    )
+    .unwrap();
+
+    assert_eq!(stderr, "");
+    assert_eq!(stdout, expected);
+}
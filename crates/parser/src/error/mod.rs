@@ -1,6 +1,6 @@
 use std::fmt;
 
-use error::InterpreterError;
+use error::{span::Span, InterpreterError};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,21 +16,46 @@ pub enum ParserErrorKind {
 
     #[error("Expected identifier.")]
     IdentifierExpected,
+
+    #[error("Invalid assignment target.")]
+    InvalidAssignmentTarget,
+
+    #[error("Expected `catch` or `finally` after `try` block.")]
+    HandlerExpected,
+
+    #[error("Expected `{0}`.")]
+    KeywordExpected(&'static str),
+
+    #[error("Expected module path string.")]
+    ModulePathExpected,
+
+    #[error("Expected variable, function or class declaration after `export`.")]
+    DeclarationExpected,
 }
 
 #[derive(Debug)]
 pub struct ParserError {
     kind: ParserErrorKind,
+    span: Span,
 }
 
 impl error::Error for ParserError {
-    fn line(&self) -> Option<usize> {
-        None
+    fn span(&self) -> Option<Span> {
+        Some(self.span)
     }
 
     fn kind(&self) -> error::ErrorKind {
         error::ErrorKind::Comptime
     }
+
+    fn help(&self) -> Option<String> {
+        match self.kind {
+            ParserErrorKind::InvalidAssignmentTarget => {
+                Some("only variables and instance fields can be assigned to".to_string())
+            }
+            _ => None,
+        }
+    }
 }
 
 impl std::error::Error for ParserError {}
@@ -41,8 +66,8 @@ impl fmt::Display for ParserError {
 }
 
 impl ParserError {
-    pub fn new(kind: ParserErrorKind) -> Self {
-        Self { kind }
+    pub fn new(kind: ParserErrorKind, span: Span) -> Self {
+        Self { kind, span }
     }
 }
 
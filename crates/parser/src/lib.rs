@@ -2,8 +2,14 @@ pub mod error;
 
 use std::{cell::RefCell, rc::Rc};
 
-use ::error::InterpreterError;
-use ast::{expression::Expression, literal::Literal, statement::Statement};
+use ::error::{span::Span, InterpreterError};
+use ast::{
+    expression::{Expression, ExpressionKind},
+    literal::Literal,
+    operator::Operator,
+    resolution::Resolution,
+    statement::{Statement, StatementKind},
+};
 use error::{ParserError, ParserErrorKind, ParserResult};
 use lexer::token::{token_type::TokenType, Token};
 
@@ -11,6 +17,7 @@ use lexer::token::{token_type::TokenType, Token};
 pub struct Parser {
     tokens: Vec<Token>,
     current: RefCell<usize>,
+    errors: RefCell<Vec<InterpreterError<ParserError>>>,
 }
 
 impl Parser {
@@ -21,31 +28,184 @@ impl Parser {
         }
     }
 
-    pub fn run(&self) -> ParserResult<Vec<Rc<Statement>>> {
-        self.program()
+    /// Parses the whole program, recovering from syntax errors at statement
+    /// boundaries. Returns every statement that parsed along with all errors met.
+    pub fn run(&self) -> (Vec<Rc<Statement>>, Vec<InterpreterError<ParserError>>) {
+        let program = self.program();
+
+        (program, self.errors.take())
     }
 
-    fn program(&self) -> ParserResult<Vec<Rc<Statement>>> {
+    fn program(&self) -> Vec<Rc<Statement>> {
         let mut statements = vec![];
 
         while !self.is_at_end() {
-            statements.push(Rc::new(self.declaration()?));
+            if let Some(statement) = self.recovering_declaration() {
+                statements.push(Rc::new(statement));
+            }
         }
 
-        Ok(statements)
+        statements
+    }
+
+    /// Parses a declaration, recording the error and skipping to the next
+    /// statement boundary if it is malformed.
+    fn recovering_declaration(&self) -> Option<Statement> {
+        let start = self.current();
+
+        match self.declaration() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                self.errors.borrow_mut().push(error);
+
+                // a token that cannot start a statement would otherwise be retried forever
+                if self.current() == start {
+                    self.advance();
+                }
+                self.synchronize();
+
+                None
+            }
+        }
     }
 
     fn declaration(&self) -> ParserResult<Statement> {
-        if self.match_token(&[TokenType::Var]) {
-            self.var_decl()
-        } else if self.match_token(&[TokenType::Fun]) {
-            self.fun_decl()
+        let start = self.peek_span();
+
+        let kind = if self.match_token(&[TokenType::Var]) {
+            self.var_decl()?
+        } else if self.check(&TokenType::Fun) && !self.check_next(&TokenType::LeftParen) {
+            self.advance();
+            self.fun_decl()?
+        } else if self.match_token(&[TokenType::Class]) {
+            self.class_decl()?
+        } else if self.match_token(&[TokenType::Export]) {
+            self.export_decl()?
+        } else if self.match_token(&[TokenType::Import]) {
+            self.import_decl()?
+        } else if self.match_token(&[TokenType::From]) {
+            self.import_from_decl()?
         } else {
-            self.statement()
+            return self.statement();
+        };
+
+        Ok(self.spanned(start, kind))
+    }
+
+    fn export_decl(&self) -> ParserResult<StatementKind> {
+        let exportable = self.check(&TokenType::Var)
+            || self.check(&TokenType::Class)
+            || (self.check(&TokenType::Fun) && !self.check_next(&TokenType::LeftParen));
+
+        if !exportable {
+            return Err(self.construct_error(ParserErrorKind::DeclarationExpected));
         }
+
+        Ok(StatementKind::Export(Box::new(self.declaration()?)))
     }
 
-    fn fun_decl(&self) -> ParserResult<Statement> {
+    fn import_decl(&self) -> ParserResult<StatementKind> {
+        let path = self.module_path()?;
+
+        if !self.match_token(&[TokenType::As]) {
+            return Err(self.construct_error(ParserErrorKind::KeywordExpected("as")));
+        }
+
+        if !self.match_token(&[TokenType::Identifier]) {
+            return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+        }
+
+        let alias = self.previous().unwrap().lexeme().to_string();
+
+        if !self.match_token(&[TokenType::Semicolon]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected(';')));
+        }
+
+        Ok(StatementKind::Import { path, alias })
+    }
+
+    fn import_from_decl(&self) -> ParserResult<StatementKind> {
+        let path = self.module_path()?;
+
+        if !self.match_token(&[TokenType::Import]) {
+            return Err(self.construct_error(ParserErrorKind::KeywordExpected("import")));
+        }
+
+        let mut names = vec![];
+
+        loop {
+            if !self.match_token(&[TokenType::Identifier]) {
+                return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+            }
+
+            names.push(self.previous().unwrap().lexeme().to_string());
+
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        if !self.match_token(&[TokenType::Semicolon]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected(';')));
+        }
+
+        Ok(StatementKind::ImportFrom { path, names })
+    }
+
+    fn module_path(&self) -> ParserResult<String> {
+        if !self.check(&TokenType::String) || self.resumes_string() {
+            return Err(self.construct_error(ParserErrorKind::ModulePathExpected));
+        }
+
+        let token = self.advance();
+        Ok(token.literal().unwrap().as_string().unwrap().to_string())
+    }
+
+    fn class_decl(&self) -> ParserResult<StatementKind> {
+        if !self.match_token(&[TokenType::Identifier]) {
+            return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+        }
+
+        let identifier = self.previous().unwrap().lexeme().to_string();
+
+        let superclass = if self.match_token(&[TokenType::Less]) {
+            if !self.match_token(&[TokenType::Identifier]) {
+                return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+            }
+
+            let token = self.previous().unwrap();
+            Some(Expression::identifier(
+                token.lexeme().to_string(),
+                token.span(),
+            ))
+        } else {
+            None
+        };
+
+        if !self.match_token(&[TokenType::LeftBrace]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
+        }
+
+        let mut methods = vec![];
+
+        while !self.is_at_end() && !self.check(&TokenType::RightBrace) {
+            let start = self.peek_span();
+            let method = self.fun_decl()?;
+            methods.push(Rc::new(self.spanned(start, method)));
+        }
+
+        if !self.match_token(&[TokenType::RightBrace]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('}')));
+        }
+
+        Ok(StatementKind::ClassDeclaration {
+            identifier,
+            superclass,
+            methods,
+        })
+    }
+
+    fn fun_decl(&self) -> ParserResult<StatementKind> {
         if !self.match_token(&[TokenType::Identifier]) {
             return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
         }
@@ -62,9 +222,9 @@ impl Parser {
             return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
         }
 
-        let execute = self.block()?;
+        let execute = self.spanned_block()?;
 
-        Ok(Statement::FunctionDeclaration {
+        Ok(StatementKind::FunctionDeclaration {
             identifier,
             parameters,
             execute: Box::new(execute),
@@ -95,7 +255,7 @@ impl Parser {
         Ok(parameters)
     }
 
-    fn var_decl(&self) -> ParserResult<Statement> {
+    fn var_decl(&self) -> ParserResult<StatementKind> {
         if !self.match_token(&[TokenType::Identifier]) {
             return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
         }
@@ -112,79 +272,213 @@ impl Parser {
             return Err(self.construct_error(ParserErrorKind::TokenExpected(';')));
         }
 
-        Ok(Statement::VariableDeclaration {
+        Ok(StatementKind::VariableDeclaration {
             identifier,
             expression,
         })
     }
 
     fn statement(&self) -> ParserResult<Statement> {
-        if self.match_token(&[TokenType::Print]) {
+        let start = self.peek_span();
+
+        let kind = if self.match_token(&[TokenType::Print]) {
             self.print_stmt()
-        } else if self.match_token(&[TokenType::LeftBrace]) {
+        } else if !self.is_map_literal() && self.match_token(&[TokenType::LeftBrace]) {
             self.block()
         } else if self.match_token(&[TokenType::If]) {
             self.if_stmt()
         } else if self.match_token(&[TokenType::While]) {
             self.while_stmt()
+        } else if self.match_token(&[TokenType::For]) {
+            self.for_stmt()
         } else if self.match_token(&[TokenType::Break]) {
             self.break_stmt()
         } else if self.match_token(&[TokenType::Continue]) {
             self.continue_stmt()
         } else if self.match_token(&[TokenType::Return]) {
             self.return_stmt()
+        } else if self.match_token(&[TokenType::Throw]) {
+            self.throw_stmt()
+        } else if self.match_token(&[TokenType::Try]) {
+            self.try_stmt()
         } else {
             self.expr_stmt()
-        }
+        }?;
+
+        Ok(self.spanned(start, kind))
+    }
+
+    /// Wraps a parsed statement with the span from `start` to the last consumed token.
+    fn spanned(&self, start: Span, kind: StatementKind) -> Statement {
+        Statement::new(kind, start.to(self.previous().unwrap().span()))
+    }
+
+    /// Parses a `{ ... }` block whose opening brace has already been consumed.
+    fn spanned_block(&self) -> ParserResult<Statement> {
+        let start = self.previous().unwrap().span();
+        let block = self.block()?;
+
+        Ok(self.spanned(start, block))
     }
 
-    fn return_stmt(&self) -> ParserResult<Statement> {
+    fn return_stmt(&self) -> ParserResult<StatementKind> {
         let expression = self.expression()?;
 
         if !self.match_token(&[TokenType::Semicolon]) {
             Err(self.construct_error(ParserErrorKind::TokenExpected(';')))
         } else {
-            Ok(Statement::Return(expression))
+            Ok(StatementKind::Return(expression))
+        }
+    }
+
+    fn break_stmt(&self) -> ParserResult<StatementKind> {
+        if !self.match_token(&[TokenType::Semicolon]) {
+            Err(self.construct_error(ParserErrorKind::TokenExpected(';')))
+        } else {
+            Ok(StatementKind::Break)
         }
     }
 
-    fn break_stmt(&self) -> ParserResult<Statement> {
+    fn continue_stmt(&self) -> ParserResult<StatementKind> {
         if !self.match_token(&[TokenType::Semicolon]) {
             Err(self.construct_error(ParserErrorKind::TokenExpected(';')))
         } else {
-            Ok(Statement::Break)
+            Ok(StatementKind::Continue)
         }
     }
 
-    fn continue_stmt(&self) -> ParserResult<Statement> {
+    fn throw_stmt(&self) -> ParserResult<StatementKind> {
+        let expression = self.expression()?;
+
         if !self.match_token(&[TokenType::Semicolon]) {
             Err(self.construct_error(ParserErrorKind::TokenExpected(';')))
         } else {
-            Ok(Statement::Continue)
+            Ok(StatementKind::Throw(expression))
         }
     }
 
-    fn while_stmt(&self) -> ParserResult<Statement> {
+    fn try_stmt(&self) -> ParserResult<StatementKind> {
+        if !self.match_token(&[TokenType::LeftBrace]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
+        }
+
+        let block = self.spanned_block()?;
+
+        let catch = if self.match_token(&[TokenType::Catch]) {
+            if !self.match_token(&[TokenType::LeftParen]) {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected('(')));
+            }
+
+            if !self.match_token(&[TokenType::Identifier]) {
+                return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+            }
+
+            let identifier = self.previous().unwrap().lexeme().to_string();
+
+            if !self.match_token(&[TokenType::RightParen]) {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected(')')));
+            }
+
+            if !self.match_token(&[TokenType::LeftBrace]) {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
+            }
+
+            Some((identifier, Box::new(self.spanned_block()?)))
+        } else {
+            None
+        };
+
+        let finally = if self.match_token(&[TokenType::Finally]) {
+            if !self.match_token(&[TokenType::LeftBrace]) {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
+            }
+
+            Some(Rc::new(self.spanned_block()?))
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(self.construct_error(ParserErrorKind::HandlerExpected));
+        }
+
+        Ok(StatementKind::Try {
+            block: Box::new(block),
+            catch,
+            finally,
+        })
+    }
+
+    fn while_stmt(&self) -> ParserResult<StatementKind> {
         let condition = self.expression()?;
 
         if !self.match_token(&[TokenType::LeftBrace]) {
             return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
         }
 
-        let block = self.block()?;
+        let block = self.spanned_block()?;
+
+        Ok(StatementKind::While {
+            condition,
+            block: Box::new(block),
+        })
+    }
+
+    fn for_stmt(&self) -> ParserResult<StatementKind> {
+        if !self.match_token(&[TokenType::LeftParen]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('(')));
+        }
+
+        let start = self.peek_span();
+        let initializer = if self.match_token(&[TokenType::Semicolon]) {
+            None
+        } else if self.match_token(&[TokenType::Var]) {
+            Some(self.var_decl()?)
+        } else {
+            Some(self.expr_stmt()?)
+        }
+        .map(|initializer| self.spanned(start, initializer));
+
+        let condition = if !self.check(&TokenType::Semicolon) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        if !self.match_token(&[TokenType::Semicolon]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected(';')));
+        }
 
-        Ok(Statement::While {
+        let increment = if !self.check(&TokenType::RightParen) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        if !self.match_token(&[TokenType::RightParen]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected(')')));
+        }
+
+        if !self.match_token(&[TokenType::LeftBrace]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
+        }
+
+        let block = self.spanned_block()?;
+
+        Ok(StatementKind::For {
+            initializer: initializer.map(Box::new),
             condition,
+            increment,
             block: Box::new(block),
         })
     }
 
-    fn if_stmt(&self) -> ParserResult<Statement> {
+    fn if_stmt(&self) -> ParserResult<StatementKind> {
         let condition = self.expression()?;
         if !self.match_token(&[TokenType::LeftBrace]) {
             return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
         }
-        let then = self.block()?;
+        let then = self.spanned_block()?;
 
         let alternative = if self.match_token(&[TokenType::Else]) {
             if !self.check(&TokenType::If) {
@@ -192,7 +486,7 @@ impl Parser {
                     return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
                 }
 
-                Some(self.block()?)
+                Some(self.spanned_block()?)
             } else {
                 Some(self.statement()?)
             }
@@ -200,42 +494,44 @@ impl Parser {
             None
         };
 
-        Ok(Statement::Conditional {
+        Ok(StatementKind::Conditional {
             condition,
             then: Box::new(then),
             alternative: alternative.map(Box::new),
         })
     }
 
-    fn block(&self) -> ParserResult<Statement> {
+    fn block(&self) -> ParserResult<StatementKind> {
         let mut statements = vec![];
 
         while !self.is_at_end() && !self.check(&TokenType::RightBrace) {
-            statements.push(Rc::new(self.declaration()?));
+            if let Some(statement) = self.recovering_declaration() {
+                statements.push(Rc::new(statement));
+            }
         }
 
         if !self.match_token(&[TokenType::RightBrace]) {
             Err(self.construct_error(ParserErrorKind::TokenExpected('}')))
         } else {
-            Ok(Statement::Block(statements))
+            Ok(StatementKind::Block(statements))
         }
     }
 
-    fn expr_stmt(&self) -> ParserResult<Statement> {
+    fn expr_stmt(&self) -> ParserResult<StatementKind> {
         let expression = self.expression()?;
 
         if self.match_token(&[TokenType::Semicolon]) {
-            Ok(Statement::Expression(expression))
+            Ok(StatementKind::Expression(expression))
         } else {
             Err(self.construct_error(ParserErrorKind::TokenExpected(';')))
         }
     }
 
-    fn print_stmt(&self) -> ParserResult<Statement> {
+    fn print_stmt(&self) -> ParserResult<StatementKind> {
         let expression = self.expression()?;
 
         if self.match_token(&[TokenType::Semicolon]) {
-            Ok(Statement::Print(expression))
+            Ok(StatementKind::Print(expression))
         } else {
             Err(self.construct_error(ParserErrorKind::TokenExpected(';')))
         }
@@ -252,17 +548,34 @@ impl Parser {
             return Ok(expr);
         }
 
-        let identifier = match expr {
-            Expression::Identifier(identifier) => identifier,
-            _ => return Err(self.construct_error(ParserErrorKind::IdentifierExpected)),
-        };
-
         let expression = self.expression()?;
+        let span = expr.span.to(expression.span);
+
+        let kind = match expr.kind {
+            ExpressionKind::Identifier { identifier, .. } => ExpressionKind::Assignment {
+                identifier,
+                expression: Box::new(expression),
+                resolution: Resolution::default(),
+            },
+            ExpressionKind::Get { object, property } => ExpressionKind::Set {
+                object,
+                property,
+                expression: Box::new(expression),
+            },
+            ExpressionKind::Index { object, index } => ExpressionKind::SetIndex {
+                object,
+                index,
+                expression: Box::new(expression),
+            },
+            _ => {
+                return Err(InterpreterError::new(ParserError::new(
+                    ParserErrorKind::InvalidAssignmentTarget,
+                    expr.span,
+                )))
+            }
+        };
 
-        Ok(Expression::Assignment {
-            identifier,
-            expression: Box::new(expression),
-        })
+        Ok(Expression::new(kind, span))
     }
 
     fn ternary(&self) -> ParserResult<Expression> {
@@ -275,12 +588,16 @@ impl Parser {
             }
 
             let alternative = self.logic_or()?;
-
-            expr = Expression::Conditional {
-                condition: Box::new(expr),
-                then: Box::new(then),
-                alternative: Box::new(alternative),
-            };
+            let span = expr.span.to(alternative.span);
+
+            expr = Expression::new(
+                ExpressionKind::Conditional {
+                    condition: Box::new(expr),
+                    then: Box::new(then),
+                    alternative: Box::new(alternative),
+                },
+                span,
+            );
         }
 
         Ok(expr)
@@ -292,11 +609,7 @@ impl Parser {
         while self.match_token(&[TokenType::Or]) {
             let operator = self.previous().unwrap().try_into().unwrap();
             let right = self.logic_and()?;
-            expr = Expression::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, operator, right);
         }
 
         Ok(expr)
@@ -308,11 +621,7 @@ impl Parser {
         while self.match_token(&[TokenType::And]) {
             let operator = self.previous().unwrap().try_into().unwrap();
             let right = self.equality()?;
-            expr = Expression::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, operator, right);
         }
 
         Ok(expr)
@@ -328,11 +637,7 @@ impl Parser {
         while self.match_token(&[TokenType::BangEqual, TokenType::EqualEqual]) {
             let operator = self.previous().unwrap().try_into().unwrap();
             let right = self.comparison()?;
-            expr = Expression::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            }
+            expr = self.binary(expr, operator, right);
         }
 
         Ok(expr)
@@ -358,18 +663,15 @@ impl Parser {
         ]) {
             let operator = self.previous().unwrap().try_into().unwrap();
             let right = self.term()?;
-            expr = Expression::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, operator, right);
         }
 
         Ok(expr)
     }
 
     fn term(&self) -> ParserResult<Expression> {
-        if self.check_many(&[TokenType::Plus, TokenType::Minus]) {
+        // a leading `-` is a negation, parsed by `unary`
+        if self.check(&TokenType::Plus) {
             return Err(self.construct_error(ParserErrorKind::MissingLeftHandOperand));
         }
 
@@ -378,11 +680,7 @@ impl Parser {
         while self.match_token(&[TokenType::Plus, TokenType::Minus]) {
             let operator = self.previous().unwrap().try_into().unwrap();
             let right = self.factor()?;
-            expr = Expression::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, operator, right);
         }
 
         Ok(expr)
@@ -398,24 +696,39 @@ impl Parser {
         while self.match_token(&[TokenType::Slash, TokenType::Star]) {
             let operator = self.previous().unwrap().try_into().unwrap();
             let right = self.unary()?;
-            expr = Expression::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            };
+            expr = self.binary(expr, operator, right);
         }
 
         Ok(expr)
     }
 
+    fn binary(&self, left: Expression, operator: Operator, right: Expression) -> Expression {
+        let span = left.span.to(right.span);
+
+        Expression::new(
+            ExpressionKind::Binary {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            },
+            span,
+        )
+    }
+
     fn unary(&self) -> ParserResult<Expression> {
         if self.match_token(&[TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous().unwrap().try_into().unwrap();
+            let token = self.previous().unwrap();
+            let operator = token.try_into().unwrap();
             let right = self.unary()?;
-            Ok(Expression::Unary {
-                operator,
-                right: Box::new(right),
-            })
+            let span = token.span().to(right.span);
+
+            Ok(Expression::new(
+                ExpressionKind::Unary {
+                    operator,
+                    right: Box::new(right),
+                },
+                span,
+            ))
         } else {
             self.call()
         }
@@ -424,21 +737,88 @@ impl Parser {
     fn call(&self) -> ParserResult<Expression> {
         let mut expr = self.primary()?;
 
-        while self.match_token(&[TokenType::LeftParen]) {
-            let arguments = self.arguments()?;
-            expr = Expression::FunctionInvokation {
-                callee: Box::new(expr),
-                arguments,
-            };
+        loop {
+            if self.match_token(&[TokenType::LeftParen]) {
+                let arguments = self.arguments()?;
 
-            if !self.match_token(&[TokenType::RightParen]) {
-                return Err(self.construct_error(ParserErrorKind::TokenExpected(')')));
+                if !self.match_token(&[TokenType::RightParen]) {
+                    return Err(self.construct_error(ParserErrorKind::TokenExpected(')')));
+                }
+
+                let span = expr.span.to(self.previous().unwrap().span());
+                expr = Expression::new(
+                    ExpressionKind::FunctionInvokation {
+                        callee: Box::new(expr),
+                        arguments,
+                    },
+                    span,
+                );
+            } else if self.match_token(&[TokenType::LeftBracket]) {
+                expr = self.index(expr)?;
+            } else if self.match_token(&[TokenType::Dot]) {
+                if !self.match_token(&[TokenType::Identifier]) {
+                    return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+                }
+
+                let token = self.previous().unwrap();
+                let span = expr.span.to(token.span());
+                expr = Expression::new(
+                    ExpressionKind::Get {
+                        object: Box::new(expr),
+                        property: token.lexeme().to_string(),
+                    },
+                    span,
+                );
+            } else {
+                break;
             }
         }
 
         Ok(expr)
     }
 
+    /// Parses `[index]` or `[start:end]` once the opening bracket has been consumed.
+    fn index(&self, object: Expression) -> ParserResult<Expression> {
+        let start = if self.check(&TokenType::Colon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        let kind = if self.match_token(&[TokenType::Colon]) {
+            let end = if self.check(&TokenType::RightBracket) {
+                None
+            } else {
+                Some(self.expression()?)
+            };
+
+            ExpressionKind::Slice {
+                object: Box::new(object),
+                start: start.map(Box::new),
+                end: end.map(Box::new),
+            }
+        } else {
+            ExpressionKind::Index {
+                object: Box::new(object),
+                // an index without a colon always has a start
+                index: Box::new(start.unwrap()),
+            }
+        };
+
+        if !self.match_token(&[TokenType::RightBracket]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected(']')));
+        }
+
+        let span = match &kind {
+            ExpressionKind::Index { object, .. } | ExpressionKind::Slice { object, .. } => {
+                object.span.to(self.previous().unwrap().span())
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(Expression::new(kind, span))
+    }
+
     fn arguments(&self) -> ParserResult<Vec<Expression>> {
         let mut arguments = vec![];
 
@@ -455,41 +835,96 @@ impl Parser {
     }
 
     fn primary(&self) -> ParserResult<Expression> {
+        let span = self.peek_span();
+
         if self.match_token(&[TokenType::False]) {
-            return Ok(Expression::Literal(Literal::Boolean(false)));
+            return Ok(Expression::new(
+                ExpressionKind::Literal(Literal::Boolean(false)),
+                span,
+            ));
         }
         if self.match_token(&[TokenType::True]) {
-            return Ok(Expression::Literal(Literal::Boolean(true)));
+            return Ok(Expression::new(
+                ExpressionKind::Literal(Literal::Boolean(true)),
+                span,
+            ));
         }
         if self.match_token(&[TokenType::Nil]) {
-            return Ok(Expression::Literal(Literal::Nil));
+            return Ok(Expression::new(ExpressionKind::Literal(Literal::Nil), span));
         }
         if self.match_token(&[TokenType::Number]) {
-            return Ok(Expression::Literal(Literal::Number(
-                self.previous()
-                    .unwrap()
-                    .literal()
-                    .unwrap()
-                    .as_number()
-                    .unwrap(),
-            )));
+            let literal = self.previous().unwrap().literal().unwrap();
+            let literal = match literal.as_integer() {
+                Some(value) => Literal::Integer(value),
+                None => Literal::Number(literal.as_number().unwrap()),
+            };
+
+            return Ok(Expression::new(ExpressionKind::Literal(literal), span));
+        }
+        if self.resumes_string() {
+            return Err(self.construct_error(ParserErrorKind::ExpressionExprected));
         }
         if self.match_token(&[TokenType::String]) {
-            return Ok(Expression::Literal(Literal::String(
-                self.previous()
-                    .unwrap()
-                    .literal()
-                    .unwrap()
-                    .as_string()
-                    .map(|s| s.to_string())
-                    .unwrap(),
-            )));
+            return Ok(Expression::new(
+                ExpressionKind::Literal(Literal::String(
+                    self.previous()
+                        .unwrap()
+                        .literal()
+                        .unwrap()
+                        .as_string()
+                        .map(|s| s.to_string())
+                        .unwrap(),
+                )),
+                span,
+            ));
         };
+        if self.match_token(&[TokenType::Interpolation]) {
+            return self.interpolation(span);
+        }
+        if self.match_token(&[TokenType::This]) {
+            return Ok(Expression::new(
+                ExpressionKind::This {
+                    resolution: Resolution::default(),
+                },
+                span,
+            ));
+        }
+        if self.match_token(&[TokenType::Super]) {
+            if !self.match_token(&[TokenType::Dot]) {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected('.')));
+            }
+
+            if !self.match_token(&[TokenType::Identifier]) {
+                return Err(self.construct_error(ParserErrorKind::IdentifierExpected));
+            }
+
+            let token = self.previous().unwrap();
+            return Ok(Expression::new(
+                ExpressionKind::Super {
+                    method: token.lexeme().to_string(),
+                    resolution: Resolution::default(),
+                },
+                span.to(token.span()),
+            ));
+        }
         if self.match_token(&[TokenType::Identifier]) {
-            return Ok(Expression::Identifier(
+            return Ok(Expression::identifier(
                 self.previous().unwrap().lexeme().to_string(),
+                span,
             ));
         }
+        if self.match_token(&[TokenType::Fun]) {
+            return self.lambda(span);
+        }
+        if self.match_token(&[TokenType::LeftBracket]) {
+            return self.list(span);
+        }
+        if self.match_token(&[TokenType::LeftBrace]) {
+            return self.map(span);
+        }
+        if self.is_arrow_function() {
+            return self.arrow_function(span);
+        }
 
         if self.match_token(&[TokenType::LeftParen]) {
             let expr = self.expression()?;
@@ -497,12 +932,219 @@ impl Parser {
                 return Err(self.construct_error(ParserErrorKind::TokenExpected(')')));
             }
 
-            return Ok(Expression::Grouping(Box::new(expr)));
+            return Ok(Expression::new(
+                ExpressionKind::Grouping(Box::new(expr)),
+                span.to(self.previous().unwrap().span()),
+            ));
         }
 
         Err(self.construct_error(ParserErrorKind::ExpressionExprected))
     }
 
+    /// Parses `[a, b, c]` once the opening bracket has been consumed. A trailing comma is allowed.
+    fn list(&self, start: Span) -> ParserResult<Expression> {
+        let mut elements = vec![];
+
+        while !self.is_at_end() && !self.check(&TokenType::RightBracket) {
+            elements.push(self.expression()?);
+
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        if !self.match_token(&[TokenType::RightBracket]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected(']')));
+        }
+
+        Ok(Expression::new(
+            ExpressionKind::List(elements),
+            start.to(self.previous().unwrap().span()),
+        ))
+    }
+
+    /// Parses `"text ${expression} text"` once the text up to the first `${` has been consumed.
+    fn interpolation(&self, start: Span) -> ParserResult<Expression> {
+        let mut parts = vec![];
+
+        loop {
+            let text = self.previous().unwrap();
+            let value = text.literal().unwrap().as_string().unwrap();
+
+            if !value.is_empty() {
+                parts.push(Expression::new(
+                    ExpressionKind::Literal(Literal::String(value.to_string())),
+                    text.span(),
+                ));
+            }
+
+            parts.push(self.expression()?);
+
+            if !self.resumes_string() {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected('}')));
+            }
+
+            if *self.advance().token_type() == TokenType::String {
+                break;
+            }
+        }
+
+        let tail = self.previous().unwrap();
+        let value = tail.literal().unwrap().as_string().unwrap();
+
+        if !value.is_empty() {
+            parts.push(Expression::new(
+                ExpressionKind::Literal(Literal::String(value.to_string())),
+                tail.span(),
+            ));
+        }
+
+        Ok(Expression::new(
+            ExpressionKind::Interpolation(parts),
+            start.to(tail.span()),
+        ))
+    }
+
+    /// Parses `{key: value, ...}` once the opening brace has been consumed. A trailing comma is allowed.
+    fn map(&self, start: Span) -> ParserResult<Expression> {
+        let mut entries = vec![];
+
+        while !self.is_at_end() && !self.check(&TokenType::RightBrace) {
+            let key = self.expression()?;
+
+            if !self.match_token(&[TokenType::Colon]) {
+                return Err(self.construct_error(ParserErrorKind::TokenExpected(':')));
+            }
+
+            entries.push((key, self.expression()?));
+
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        if !self.match_token(&[TokenType::RightBrace]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('}')));
+        }
+
+        Ok(Expression::new(
+            ExpressionKind::Map(entries),
+            start.to(self.previous().unwrap().span()),
+        ))
+    }
+
+    /// Parses `fun (parameters) { ... }` once `fun` has been consumed.
+    fn lambda(&self, start: Span) -> ParserResult<Expression> {
+        if !self.match_token(&[TokenType::LeftParen]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('(')));
+        }
+
+        let parameters = self.parameters()?;
+
+        if !self.match_token(&[TokenType::LeftBrace]) {
+            return Err(self.construct_error(ParserErrorKind::TokenExpected('{')));
+        }
+
+        let execute = self.spanned_block()?;
+        let span = start.to(execute.span);
+
+        Ok(Expression::new(
+            ExpressionKind::Function {
+                parameters,
+                execute: Box::new(execute),
+            },
+            span,
+        ))
+    }
+
+    /// Parses `(parameters) => body`, where the body is a block or a single
+    /// expression standing for a block returning it.
+    fn arrow_function(&self, start: Span) -> ParserResult<Expression> {
+        self.advance();
+        let parameters = self.parameters()?;
+        self.advance();
+
+        let execute = if !self.is_map_literal() && self.match_token(&[TokenType::LeftBrace]) {
+            self.spanned_block()?
+        } else {
+            let expression = self.expression()?;
+            let span = expression.span;
+
+            Statement::new(
+                StatementKind::Block(vec![Rc::new(Statement::new(
+                    StatementKind::Return(expression),
+                    span,
+                ))]),
+                span,
+            )
+        };
+        let span = start.to(execute.span);
+
+        Ok(Expression::new(
+            ExpressionKind::Function {
+                parameters,
+                execute: Box::new(execute),
+            },
+            span,
+        ))
+    }
+
+    /// Whether the upcoming token is string text resuming after an interpolated expression. Its
+    /// lexeme starts at the closing `}`, so `"${a "b"}"` is missing a brace rather than
+    /// interpolating `"b"`.
+    fn resumes_string(&self) -> bool {
+        self.check_many(&[TokenType::Interpolation, TokenType::String])
+            && self.peek().unwrap().lexeme().starts_with('}')
+    }
+
+    /// Whether the upcoming `{` opens a map literal rather than a block, where both
+    /// are allowed. Only a key made of a single token followed by `:` is recognized,
+    /// which can never start a statement; `{}` stays an empty block.
+    fn is_map_literal(&self) -> bool {
+        self.check(&TokenType::LeftBrace)
+            && self.tokens.get(self.current() + 1).is_some_and(|token| {
+                matches!(
+                    token.token_type(),
+                    TokenType::String
+                        | TokenType::Number
+                        | TokenType::Identifier
+                        | TokenType::True
+                        | TokenType::False
+                        | TokenType::Nil
+                )
+            })
+            && self
+                .tokens
+                .get(self.current() + 2)
+                .is_some_and(|token| *token.token_type() == TokenType::Colon)
+    }
+
+    /// Whether the upcoming `(` opens the parameter list of an arrow function
+    /// rather than a grouping.
+    fn is_arrow_function(&self) -> bool {
+        if !self.check(&TokenType::LeftParen) {
+            return false;
+        }
+
+        let mut tokens = self.tokens[self.current() + 1..]
+            .iter()
+            .map(Token::token_type);
+
+        loop {
+            match tokens.next() {
+                Some(TokenType::RightParen) => break,
+                Some(TokenType::Identifier) => match tokens.next() {
+                    Some(TokenType::Comma) => continue,
+                    Some(TokenType::RightParen) => break,
+                    _ => return false,
+                },
+                _ => return false,
+            }
+        }
+
+        tokens.next() == Some(&TokenType::Arrow)
+    }
+
     fn match_token(&self, tokens: &[TokenType]) -> bool {
         for token_type in tokens {
             if self.check(token_type) {
@@ -514,25 +1156,49 @@ impl Parser {
         false
     }
 
+    /// Skips tokens up to the end of the current statement. Stops before a
+    /// closing brace so the enclosing block can still be terminated, and skips
+    /// nested blocks whole.
     fn synchronize(&self) {
-        self.advance();
+        let mut depth = 0;
 
         while !self.is_at_end() {
-            if *self.previous().unwrap().token_type() == TokenType::Semicolon {
-                break;
-            }
-
-            match self.advance().token_type() {
+            match self.peek().unwrap().token_type() {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => return,
+                TokenType::RightBrace => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                }
+                TokenType::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
                 TokenType::Class
-                | TokenType::If
+                | TokenType::Fun
                 | TokenType::Var
+                | TokenType::For
+                | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::For
                 | TokenType::Return
-                | TokenType::Fun => break,
+                | TokenType::Throw
+                | TokenType::Try
+                | TokenType::Import
+                | TokenType::Export
+                | TokenType::From
+                    if depth == 0 =>
+                {
+                    return
+                }
                 _ => (),
             }
+
+            self.advance();
         }
     }
 
@@ -542,6 +1208,13 @@ impl Parser {
             .unwrap_or(false)
     }
 
+    fn check_next(&self, token_type: &TokenType) -> bool {
+        self.tokens
+            .get(self.current() + 1)
+            .map(|token| token.token_type() == token_type)
+            .unwrap_or(false)
+    }
+
     fn check_many(&self, token_type: &[TokenType]) -> bool {
         self.peek()
             .map(|token| {
@@ -563,6 +1236,20 @@ impl Parser {
         self.tokens.get(self.current())
     }
 
+    /// Span of the next token. The end of input is placed right after the last token,
+    /// so errors there point at the line they are about rather than past it.
+    fn peek_span(&self) -> Span {
+        let last = self
+            .current()
+            .checked_sub(1)
+            .and_then(|last| self.tokens.get(last));
+
+        match last {
+            Some(last) if self.is_at_end() => end_of(last),
+            _ => self.peek().map(Token::span).unwrap_or_default(),
+        }
+    }
+
     fn advance(&self) -> &Token {
         self.advance_by(1);
         self.previous().unwrap()
@@ -581,8 +1268,24 @@ impl Parser {
     }
 }
 
+/// Empty span just past `token`.
+fn end_of(token: &Token) -> Span {
+    let span = token.span();
+    let lexeme = token.lexeme();
+
+    let (line, column) = match lexeme.rfind('\n') {
+        Some(newline) => (
+            span.line + lexeme.matches('\n').count(),
+            lexeme[newline + 1..].chars().count() + 1,
+        ),
+        None => (span.line, span.column + lexeme.chars().count()),
+    };
+
+    Span::new(span.end, span.end, line, column)
+}
+
 impl Parser {
     fn construct_error(&self, kind: ParserErrorKind) -> InterpreterError<ParserError> {
-        InterpreterError::new(ParserError::new(kind))
+        InterpreterError::new(ParserError::new(kind, self.peek_span()))
     }
 }
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use error::{diagnostic::Label, span::Span, InterpreterError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ResolverErrorKind {
+    #[error("Cannot read local variable `{0}` in its own initializer.")]
+    ReadInOwnInitializer(String),
+
+    #[error("Variable with identifier `{0}` is already defined in this scope.")]
+    VariableAlreadyDefined(String),
+
+    #[error("`return` statement used outside of a function.")]
+    ReturnNotWithinFunction,
+
+    #[error("`break` statement used outside of a loop.")]
+    BreakNotWithinLoop,
+
+    #[error("`continue` statement used outside of a loop.")]
+    ContinueNotWithinLoop,
+
+    #[error("`this` used outside of a class.")]
+    ThisNotWithinClass,
+
+    #[error("`super` used outside of a class.")]
+    SuperNotWithinClass,
+
+    #[error("`super` used in a class with no superclass.")]
+    SuperWithoutSuperclass,
+
+    #[error("Class `{0}` cannot inherit from itself.")]
+    InheritsFromItself(String),
+
+    #[error("`export` used outside of the top level of a module.")]
+    ExportNotAtTopLevel,
+}
+
+#[derive(Debug)]
+pub struct ResolverError {
+    kind: ResolverErrorKind,
+    span: Span,
+    labels: Vec<Label>,
+}
+
+impl error::Error for ResolverError {
+    fn span(&self) -> Option<Span> {
+        Some(self.span)
+    }
+
+    fn kind(&self) -> error::ErrorKind {
+        error::ErrorKind::Comptime
+    }
+
+    fn labels(&self) -> Vec<Label> {
+        self.labels.clone()
+    }
+
+    fn help(&self) -> Option<String> {
+        match &self.kind {
+            ResolverErrorKind::ReadInOwnInitializer(_) => Some(
+                "declare the new variable under a different name to use the outer one".to_string(),
+            ),
+            ResolverErrorKind::VariableAlreadyDefined(_) => {
+                Some("use a different name, or assign to the existing variable".to_string())
+            }
+            ResolverErrorKind::SuperWithoutSuperclass => {
+                Some("declare a superclass with `class Name < Superclass`".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for ResolverError {}
+impl fmt::Display for ResolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl ResolverError {
+    pub fn new(kind: ResolverErrorKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            labels: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+}
+
+pub type ResolverResult<T> = Result<T, InterpreterError<ResolverError>>;
@@ -0,0 +1,525 @@
+pub mod error;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use ::error::{diagnostic::Label, span::Span, InterpreterError};
+use ast::{
+    expression::{Expression, ExpressionKind},
+    resolution::Resolution,
+    statement::{Statement, StatementKind},
+};
+use error::{ResolverError, ResolverErrorKind, ResolverResult};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum FunctionKind {
+    #[default]
+    None,
+    Function,
+    Method,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum ClassKind {
+    #[default]
+    None,
+    Class,
+    Subclass,
+}
+
+#[derive(Debug)]
+struct Variable {
+    slot: usize,
+    defined: bool,
+    span: Span,
+}
+
+/// Static pass run between the parser and the runtime.
+///
+/// Every local variable access is annotated with the `(depth, slot)` pair the
+/// runtime uses to find it, so the scopes opened here have to mirror the
+/// environments created by the runtime one-to-one.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    scopes: RefCell<Vec<HashMap<String, Variable>>>,
+    function: Cell<FunctionKind>,
+    class: Cell<ClassKind>,
+    loops: Cell<usize>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn run(&self, program: &[Rc<Statement>]) -> ResolverResult<()> {
+        for stmt in program {
+            self.statement(stmt)?;
+        }
+
+        Ok(())
+    }
+
+    fn statement(&self, stmt: &Statement) -> ResolverResult<()> {
+        match &stmt.kind {
+            StatementKind::Print(expr) | StatementKind::Expression(expr) => self.expression(expr),
+            StatementKind::VariableDeclaration {
+                identifier,
+                expression,
+            } => {
+                self.declare(identifier, stmt.span)?;
+                self.expression(expression)?;
+                self.define(identifier);
+
+                Ok(())
+            }
+            StatementKind::FunctionDeclaration {
+                identifier,
+                parameters,
+                execute,
+            } => {
+                self.declare(identifier, stmt.span)?;
+                self.define(identifier);
+
+                self.function(parameters, execute, stmt.span, FunctionKind::Function)
+            }
+            StatementKind::ClassDeclaration {
+                identifier,
+                superclass,
+                methods,
+            } => self.class(identifier, superclass.as_ref(), methods, stmt.span),
+            StatementKind::Block(statements) => {
+                self.begin_scope();
+                self.run(statements)?;
+                self.end_scope();
+
+                Ok(())
+            }
+            StatementKind::Conditional {
+                condition,
+                then,
+                alternative,
+            } => {
+                self.expression(condition)?;
+                self.statement(then)?;
+
+                if let Some(alternative) = alternative {
+                    self.statement(alternative)?;
+                }
+
+                Ok(())
+            }
+            StatementKind::While { condition, block } => {
+                self.expression(condition)?;
+                self.loop_body(block)
+            }
+            StatementKind::For {
+                initializer,
+                condition,
+                increment,
+                block,
+            } => {
+                self.begin_scope();
+
+                if let Some(initializer) = initializer {
+                    self.statement(initializer)?;
+                }
+                if let Some(condition) = condition {
+                    self.expression(condition)?;
+                }
+                if let Some(increment) = increment {
+                    self.expression(increment)?;
+                }
+
+                self.loop_body(block)?;
+                self.end_scope();
+
+                Ok(())
+            }
+            StatementKind::Break => {
+                if self.loops.get() == 0 {
+                    return Err(
+                        self.construct_error(ResolverErrorKind::BreakNotWithinLoop, stmt.span)
+                    );
+                }
+
+                Ok(())
+            }
+            StatementKind::Continue => {
+                if self.loops.get() == 0 {
+                    return Err(
+                        self.construct_error(ResolverErrorKind::ContinueNotWithinLoop, stmt.span)
+                    );
+                }
+
+                Ok(())
+            }
+            StatementKind::Return(expression) => {
+                if self.function.get() == FunctionKind::None {
+                    return Err(
+                        self.construct_error(ResolverErrorKind::ReturnNotWithinFunction, stmt.span)
+                    );
+                }
+
+                self.expression(expression)
+            }
+            StatementKind::Throw(expression) => self.expression(expression),
+            StatementKind::Try {
+                block,
+                catch,
+                finally,
+            } => {
+                self.statement(block)?;
+
+                if let Some((identifier, handler)) = catch {
+                    self.begin_scope();
+                    self.declare(identifier, stmt.span)?;
+                    self.define(identifier);
+                    self.statement(handler)?;
+                    self.end_scope();
+                }
+
+                if let Some(finally) = finally {
+                    self.statement(finally)?;
+                }
+
+                Ok(())
+            }
+            StatementKind::Import { alias, .. } => {
+                self.declare(alias, stmt.span)?;
+                self.define(alias);
+
+                Ok(())
+            }
+            StatementKind::ImportFrom { names, .. } => {
+                for name in names {
+                    self.declare(name, stmt.span)?;
+                    self.define(name);
+                }
+
+                Ok(())
+            }
+            StatementKind::Export(declaration) => {
+                if !self.scopes.borrow().is_empty() {
+                    return Err(
+                        self.construct_error(ResolverErrorKind::ExportNotAtTopLevel, stmt.span)
+                    );
+                }
+
+                self.statement(declaration)
+            }
+        }
+    }
+
+    fn loop_body(&self, block: &Statement) -> ResolverResult<()> {
+        self.loops.set(self.loops.get() + 1);
+        self.statement(block)?;
+        self.loops.set(self.loops.get() - 1);
+
+        Ok(())
+    }
+
+    fn class(
+        &self,
+        identifier: &str,
+        superclass: Option<&Expression>,
+        methods: &[Rc<Statement>],
+        span: Span,
+    ) -> ResolverResult<()> {
+        let enclosing_class = self.class.replace(ClassKind::Class);
+
+        self.declare(identifier, span)?;
+        self.define(identifier);
+
+        if let Some(superclass) = superclass {
+            if let ExpressionKind::Identifier {
+                identifier: superclass_identifier,
+                ..
+            } = &superclass.kind
+            {
+                if superclass_identifier == identifier {
+                    return Err(self.construct_error(
+                        ResolverErrorKind::InheritsFromItself(identifier.to_string()),
+                        superclass.span,
+                    ));
+                }
+            }
+
+            self.class.set(ClassKind::Subclass);
+            self.expression(superclass)?;
+
+            self.begin_scope();
+            self.declare("super", span)?;
+            self.define("super");
+        }
+
+        // methods are bound to their instance in an environment of their own
+        self.begin_scope();
+        self.declare("this", span)?;
+        self.define("this");
+
+        for method in methods {
+            if let StatementKind::FunctionDeclaration {
+                parameters,
+                execute,
+                ..
+            } = &method.kind
+            {
+                self.function(parameters, execute, method.span, FunctionKind::Method)?;
+            }
+        }
+
+        self.end_scope();
+
+        if superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.class.set(enclosing_class);
+
+        Ok(())
+    }
+
+    fn function(
+        &self,
+        parameters: &[String],
+        execute: &Statement,
+        span: Span,
+        kind: FunctionKind,
+    ) -> ResolverResult<()> {
+        let enclosing_function = self.function.replace(kind);
+        let enclosing_loops = self.loops.replace(0);
+
+        self.begin_scope();
+
+        for parameter in parameters {
+            self.declare(parameter, span)?;
+            self.define(parameter);
+        }
+
+        self.statement(execute)?;
+        self.end_scope();
+
+        self.function.set(enclosing_function);
+        self.loops.set(enclosing_loops);
+
+        Ok(())
+    }
+
+    fn expression(&self, expr: &Expression) -> ResolverResult<()> {
+        match &expr.kind {
+            ExpressionKind::Binary { left, right, .. } => {
+                self.expression(left)?;
+                self.expression(right)
+            }
+            ExpressionKind::Unary { right, .. } => self.expression(right),
+            ExpressionKind::Literal(_) => Ok(()),
+            ExpressionKind::Function {
+                parameters,
+                execute,
+            } => self.function(parameters, execute, expr.span, FunctionKind::Function),
+            ExpressionKind::Grouping(expr) => self.expression(expr),
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                alternative,
+            } => {
+                self.expression(condition)?;
+                self.expression(then)?;
+                self.expression(alternative)
+            }
+            ExpressionKind::Identifier {
+                identifier,
+                resolution,
+            } => {
+                let declared_only = self
+                    .scopes
+                    .borrow()
+                    .last()
+                    .and_then(|scope| scope.get(identifier))
+                    .map(|variable| !variable.defined)
+                    .unwrap_or(false);
+
+                if declared_only {
+                    return Err(self.construct_error(
+                        ResolverErrorKind::ReadInOwnInitializer(identifier.to_string()),
+                        expr.span,
+                    ));
+                }
+
+                self.resolve_local(identifier, resolution);
+
+                Ok(())
+            }
+            ExpressionKind::Assignment {
+                identifier,
+                expression,
+                resolution,
+            } => {
+                self.expression(expression)?;
+                self.resolve_local(identifier, resolution);
+
+                Ok(())
+            }
+            ExpressionKind::FunctionInvokation { callee, arguments } => {
+                self.expression(callee)?;
+
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+
+                Ok(())
+            }
+            ExpressionKind::Get { object, .. } => self.expression(object),
+            ExpressionKind::List(elements) => {
+                for element in elements {
+                    self.expression(element)?;
+                }
+
+                Ok(())
+            }
+            ExpressionKind::Interpolation(parts) => {
+                for part in parts {
+                    self.expression(part)?;
+                }
+
+                Ok(())
+            }
+            ExpressionKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.expression(key)?;
+                    self.expression(value)?;
+                }
+
+                Ok(())
+            }
+            ExpressionKind::Index { object, index } => {
+                self.expression(object)?;
+                self.expression(index)
+            }
+            ExpressionKind::SetIndex {
+                object,
+                index,
+                expression,
+            } => {
+                self.expression(object)?;
+                self.expression(index)?;
+                self.expression(expression)
+            }
+            ExpressionKind::Slice { object, start, end } => {
+                self.expression(object)?;
+
+                for bound in [start, end].into_iter().flatten() {
+                    self.expression(bound)?;
+                }
+
+                Ok(())
+            }
+            ExpressionKind::Set {
+                object, expression, ..
+            } => {
+                self.expression(expression)?;
+                self.expression(object)
+            }
+            ExpressionKind::This { resolution } => {
+                if self.class.get() == ClassKind::None {
+                    return Err(
+                        self.construct_error(ResolverErrorKind::ThisNotWithinClass, expr.span)
+                    );
+                }
+
+                self.resolve_local("this", resolution);
+
+                Ok(())
+            }
+            ExpressionKind::Super { resolution, .. } => match self.class.get() {
+                ClassKind::None => {
+                    Err(self.construct_error(ResolverErrorKind::SuperNotWithinClass, expr.span))
+                }
+                ClassKind::Class => {
+                    Err(self.construct_error(ResolverErrorKind::SuperWithoutSuperclass, expr.span))
+                }
+                ClassKind::Subclass => {
+                    self.resolve_local("super", resolution);
+
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    fn resolve_local(&self, identifier: &str, resolution: &Resolution) {
+        let scopes = self.scopes.borrow();
+
+        if let Some((depth, variable)) = scopes
+            .iter()
+            .rev()
+            .enumerate()
+            .find_map(|(depth, scope)| scope.get(identifier).map(|variable| (depth, variable)))
+        {
+            resolution.resolve(depth, variable.slot);
+        }
+    }
+
+    fn declare(&self, identifier: &str, span: Span) -> ResolverResult<()> {
+        let mut scopes = self.scopes.borrow_mut();
+
+        let Some(scope) = scopes.last_mut() else {
+            return Ok(());
+        };
+
+        if let Some(previous) = scope.get(identifier) {
+            return Err(InterpreterError::new(
+                ResolverError::new(
+                    ResolverErrorKind::VariableAlreadyDefined(identifier.to_string()),
+                    span,
+                )
+                .with_label(Label::new(previous.span, "variable first declared here")),
+            ));
+        }
+
+        let slot = scope.len();
+        scope.insert(
+            identifier.to_string(),
+            Variable {
+                slot,
+                defined: false,
+                span,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn define(&self, identifier: &str) {
+        if let Some(variable) = self
+            .scopes
+            .borrow_mut()
+            .last_mut()
+            .and_then(|scope| scope.get_mut(identifier))
+        {
+            variable.defined = true;
+        }
+    }
+
+    fn begin_scope(&self) {
+        self.scopes.borrow_mut().push(HashMap::new());
+    }
+
+    fn end_scope(&self) {
+        self.scopes.borrow_mut().pop();
+    }
+}
+
+impl Resolver {
+    fn construct_error(
+        &self,
+        kind: ResolverErrorKind,
+        span: Span,
+    ) -> InterpreterError<ResolverError> {
+        InterpreterError::new(ResolverError::new(kind, span))
+    }
+}
@@ -0,0 +1,140 @@
+use std::rc::Rc;
+
+use ast::{
+    expression::ExpressionKind,
+    statement::{Statement, StatementKind},
+};
+use error::{span::Span, Error as _};
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::{error::ResolverResult, Resolver};
+
+fn parse(source: &str) -> Vec<Rc<Statement>> {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+
+    program
+}
+
+fn resolve(source: &str) -> ResolverResult<Vec<Rc<Statement>>> {
+    let program = parse(source);
+    Resolver::new().run(&program).map(|_| program)
+}
+
+/// Message and location of the error reported for `source`.
+fn error(source: &str) -> (String, Span) {
+    let error = resolve(source).expect_err("program should not resolve");
+    let error = error.source();
+
+    (error.to_string(), error.span().unwrap())
+}
+
+fn block(statement: &Statement) -> &[Rc<Statement>] {
+    match &statement.kind {
+        StatementKind::Block(statements) => statements,
+        kind => panic!("expected a block, found {kind:?}"),
+    }
+}
+
+#[test]
+fn locals_resolve_to_depth_and_slot() {
+    let program = resolve("{ var a = 1; var b = 2; { print b; print c; } }").unwrap();
+
+    let inner = block(&block(&program[0])[2]);
+    let resolutions = inner
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::Print(expression) => match &expression.kind {
+                ExpressionKind::Identifier { resolution, .. } => resolution.get(),
+                kind => panic!("expected an identifier, found {kind:?}"),
+            },
+            kind => panic!("expected a print, found {kind:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    // globals stay unresolved and are looked up by name
+    assert_eq!(resolutions, [Some((1, 1)), None]);
+}
+
+#[test]
+fn globals_can_be_redeclared() {
+    assert!(resolve("var a = 1; var a = 2;").is_ok());
+}
+
+#[test]
+fn local_redeclaration_points_at_first_declaration() {
+    let source = "{ var a = 1; var a = 2; }";
+    let error = resolve(source).unwrap_err();
+    let error = error.source();
+
+    assert_eq!(
+        error.to_string(),
+        "Variable with identifier `a` is already defined in this scope."
+    );
+    assert_eq!(error.span().unwrap().column, 14);
+
+    let labels = error.labels();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].span.column, 3);
+    assert_eq!(labels[0].message, "variable first declared here");
+    assert!(error.help().is_some());
+}
+
+#[test]
+fn local_read_in_own_initializer() {
+    let (message, span) = error("{ var a = 1; { var a = a; } }");
+
+    assert_eq!(
+        message,
+        "Cannot read local variable `a` in its own initializer."
+    );
+    assert_eq!(span.column, 24);
+}
+
+#[test]
+fn jumps_outside_their_construct() {
+    assert_eq!(
+        error("return 1;").0,
+        "`return` statement used outside of a function."
+    );
+    assert_eq!(
+        error("break;").0,
+        "`break` statement used outside of a loop."
+    );
+    assert_eq!(
+        error("while (true) { fun f() { continue; } }").0,
+        "`continue` statement used outside of a loop."
+    );
+}
+
+#[test]
+fn this_and_super_outside_classes() {
+    assert_eq!(error("print this;").0, "`this` used outside of a class.");
+    assert_eq!(
+        error("print super.f;").0,
+        "`super` used outside of a class."
+    );
+    assert_eq!(
+        error("class A { f() { return super.f(); } }").0,
+        "`super` used in a class with no superclass."
+    );
+}
+
+#[test]
+fn class_inheriting_from_itself() {
+    let (message, span) = error("class A < A {}");
+
+    assert_eq!(message, "Class `A` cannot inherit from itself.");
+    assert_eq!(span.column, 11);
+}
+
+#[test]
+fn export_inside_block() {
+    assert_eq!(
+        error("{ export var a = 1; }").0,
+        "`export` used outside of the top level of a module."
+    );
+}
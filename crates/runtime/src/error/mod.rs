@@ -1,8 +1,10 @@
-use std::fmt;
+use std::{fmt, io, path::Path, rc::Rc};
 
-use error::InterpreterError;
+use error::{span::Span, InterpreterError};
 use thiserror::Error;
 
+use crate::runtime::{capabilities::Capability, module::ModuleFile, value::RuntimeValue};
+
 #[derive(Debug, Error)]
 pub enum RuntimeErrorKind {
     #[error("Operand must be a number.")]
@@ -31,16 +33,200 @@ pub enum RuntimeErrorKind {
 
     #[error("`return` statement used outside of a function.")]
     ReturnNotWithinFunction,
+
+    #[error("Only instances have properties.")]
+    ExpressionNotInstance,
+
+    #[error("Undefined property `{0}`.")]
+    UndefinedProperty(String),
+
+    #[error("Superclass must be a class.")]
+    SuperclassNotClass,
+
+    #[error("Function `{0}` expected {1}.")]
+    InvalidArgumentType(String, String),
+
+    #[error("Assertion failed: {0}")]
+    AssertionFailed(String),
+
+    #[error("Value cannot be passed to or from a native function.")]
+    UnsupportedNativeValue,
+
+    #[error("I/O failure: {0}.")]
+    Io(io::Error),
+
+    #[error("Maximum call depth of {0} exceeded.")]
+    StackOverflow(usize),
+
+    #[error("Execution budget of {0} steps exhausted.")]
+    BudgetExhausted(u64),
+
+    #[error("Execution timed out.")]
+    Timeout,
+
+    #[error("Memory limit of {0} bytes exceeded.")]
+    OutOfMemory(usize),
+
+    #[error("Could not allocate {0} bytes.")]
+    AllocationFailed(usize),
+
+    #[error("The `{0}` limit is not supported by this backend.")]
+    UnsupportedLimit(&'static str),
+
+    #[error("Only lists and maps can be indexed.")]
+    ExpressionNotIndexable,
+
+    #[error("Only lists can be sliced.")]
+    ExpressionNotSliceable,
+
+    #[error("Index must be an integer.")]
+    IndexNotInteger,
+
+    #[error("Index {0} is out of bounds for a list of length {1}.")]
+    IndexOutOfBounds(i64, usize),
+
+    #[error("Map keys must be nil, booleans, strings or numbers other than NaN.")]
+    InvalidMapKey,
+
+    #[error("Key {0} is not in the map.")]
+    KeyNotFound(String),
+
+    #[error("Cannot convert {0:?} to a number.")]
+    InvalidNumber(String),
+
+    #[error("`{0}` needs the `{1}` capability, which was not granted.")]
+    PermissionDenied(String, Capability),
+
+    #[error("Program exited with code {0}.")]
+    Exit(i32),
+
+    #[error("Uncaught exception: {0}")]
+    UncaughtException(String),
+
+    #[error("Module `{0}` not found.")]
+    ModuleNotFound(String),
+
+    #[error("Module `{0}` could not be compiled: {1}")]
+    InvalidModule(String, String),
+
+    #[error("Import cycle: {0}.")]
+    ImportCycle(String),
+
+    #[error("Module `{0}` does not export `{1}`.")]
+    UndefinedExport(String, String),
+}
+
+impl RuntimeErrorKind {
+    /// Name of the variant, exposed to Lox code as the `kind` of a caught error.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ExpectedNumberOperand => "ExpectedNumberOperand",
+            Self::ZeroDivision => "ZeroDivision",
+            Self::VariableAlreadyDefined(_) => "VariableAlreadyDefined",
+            Self::VariableNotDefined(_) => "VariableNotDefined",
+            Self::ContinueNotWithinLoop => "ContinueNotWithinLoop",
+            Self::BreakNotWithinLoop => "BreakNotWithinLoop",
+            Self::ExpressionNotCallable => "ExpressionNotCallable",
+            Self::InvalidArgumentCount(..) => "InvalidArgumentCount",
+            Self::ReturnNotWithinFunction => "ReturnNotWithinFunction",
+            Self::ExpressionNotInstance => "ExpressionNotInstance",
+            Self::UndefinedProperty(_) => "UndefinedProperty",
+            Self::SuperclassNotClass => "SuperclassNotClass",
+            Self::InvalidArgumentType(..) => "InvalidArgumentType",
+            Self::AssertionFailed(_) => "AssertionFailed",
+            Self::UnsupportedNativeValue => "UnsupportedNativeValue",
+            Self::Io(_) => "Io",
+            Self::StackOverflow(_) => "StackOverflow",
+            Self::BudgetExhausted(_) => "BudgetExhausted",
+            Self::Timeout => "Timeout",
+            Self::OutOfMemory(_) => "OutOfMemory",
+            Self::AllocationFailed(_) => "AllocationFailed",
+            Self::UnsupportedLimit(_) => "UnsupportedLimit",
+            Self::ExpressionNotIndexable => "ExpressionNotIndexable",
+            Self::ExpressionNotSliceable => "ExpressionNotSliceable",
+            Self::IndexNotInteger => "IndexNotInteger",
+            Self::IndexOutOfBounds(..) => "IndexOutOfBounds",
+            Self::InvalidMapKey => "InvalidMapKey",
+            Self::KeyNotFound(_) => "KeyNotFound",
+            Self::InvalidNumber(_) => "InvalidNumber",
+            Self::PermissionDenied(..) => "PermissionDenied",
+            Self::Exit(_) => "Exit",
+            Self::UncaughtException(_) => "UncaughtException",
+            Self::ModuleNotFound(_) => "ModuleNotFound",
+            Self::InvalidModule(..) => "InvalidModule",
+            Self::ImportCycle(_) => "ImportCycle",
+            Self::UndefinedExport(..) => "UndefinedExport",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct RuntimeError {
     kind: RuntimeErrorKind,
+    span: Option<Span>,
+    /// Module the span points into, `None` for the program being run.
+    file: Option<Rc<ModuleFile>>,
+    /// Value of a `throw` that left a function, so an enclosing `try` can still catch it.
+    thrown: Option<Rc<RuntimeValue>>,
 }
 
 impl RuntimeError {
     pub fn new(kind: RuntimeErrorKind) -> Self {
-        Self { kind }
+        Self {
+            kind,
+            span: None,
+            file: None,
+            thrown: None,
+        }
+    }
+
+    /// Error for a thrown `value` that no `try` statement caught.
+    pub fn uncaught(value: Rc<RuntimeValue>, description: String) -> Self {
+        Self {
+            thrown: Some(value),
+            ..Self::new(RuntimeErrorKind::UncaughtException(description))
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.kind.name()
+    }
+
+    /// Whether a `try` statement can catch the error. Running out of resources
+    /// and calling `exit` always end the program.
+    pub fn is_catchable(&self) -> bool {
+        !matches!(
+            self.kind,
+            RuntimeErrorKind::BudgetExhausted(_)
+                | RuntimeErrorKind::Timeout
+                | RuntimeErrorKind::OutOfMemory(_)
+                | RuntimeErrorKind::Exit(_)
+        )
+    }
+
+    /// Code the program asked to exit with, if it stopped by calling `exit`.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self.kind {
+            RuntimeErrorKind::Exit(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    pub fn take_thrown(&mut self) -> Option<Rc<RuntimeValue>> {
+        self.thrown.take()
+    }
+
+    /// Attaches `span` unless a more precise one was already recorded.
+    pub fn locate(&mut self, span: Span) {
+        self.locate_in(span, None);
+    }
+
+    /// Like [`RuntimeError::locate`], for a span in the module `file`.
+    pub fn locate_in(&mut self, span: Span, file: Option<&Rc<ModuleFile>>) {
+        if self.span.is_none() {
+            self.span = Some(span);
+            self.file = file.cloned();
+        }
     }
 }
 
@@ -51,13 +237,40 @@ impl fmt::Display for RuntimeError {
 }
 impl std::error::Error for RuntimeError {}
 impl error::Error for RuntimeError {
-    fn line(&self) -> Option<usize> {
-        None
+    fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    fn file(&self) -> Option<&Path> {
+        self.file.as_ref().map(|file| file.path.as_path())
+    }
+
+    fn file_source(&self) -> Option<&str> {
+        self.file.as_ref().map(|file| file.source.as_str())
     }
 
     fn kind(&self) -> error::ErrorKind {
         error::ErrorKind::Runtime
     }
+
+    fn help(&self) -> Option<String> {
+        match &self.kind {
+            RuntimeErrorKind::VariableNotDefined(identifier) => Some(format!(
+                "declare it with `var {identifier} = ...;` before using it"
+            )),
+            RuntimeErrorKind::ExpressionNotCallable => {
+                Some("only functions and classes can be called".to_string())
+            }
+            RuntimeErrorKind::PermissionDenied(_, capability) => Some(format!(
+                "the host has to grant it, e.g. with `--allow-{capability}` on the command line"
+            )),
+            RuntimeErrorKind::ModuleNotFound(_) => Some(
+                "paths are relative to the importing file, then to each module search directory"
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
 }
 
 pub type RuntimeResult<T> = Result<T, InterpreterError<RuntimeError>>;
@@ -1,42 +1,431 @@
 pub mod error;
 pub mod runtime;
 
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Instant,
+};
 
-use ::error::InterpreterError;
-use ast::{expression::Expression, literal::Literal, operator::Operator, statement::Statement};
+use ::error::{span::Span, Error as _, InterpreterError};
+use ast::{
+    expression::{Expression, ExpressionKind},
+    literal::Literal,
+    operator::Operator,
+    statement::{Statement, StatementKind},
+};
 use error::{RuntimeError, RuntimeErrorKind, RuntimeResult};
-use runtime::{environment::Environment, signal::RuntimeSignal, value::RuntimeValue};
+use runtime::{
+    capabilities::{Capabilities, Capability},
+    environment::Environment,
+    heap::{GcStats, Heap},
+    limits::Limits,
+    list::{self, BoundListMethod, ListMethod},
+    map::{self, BoundMapMethod, Entries, MapKey, MapMethod},
+    module::{self, ModuleFile, ModuleLoader},
+    native::{self, Library, NativeFunction},
+    signal::RuntimeSignal,
+    string::{BoundStringMethod, StringMethod},
+    value::RuntimeValue,
+};
 
 pub struct Runtime {
+    globals: Rc<Environment>,
     environment: RefCell<Rc<Environment>>,
+    output: RefCell<Box<dyn Write>>,
+    input: RefCell<Box<dyn BufRead>>,
+    limits: Cell<Limits>,
+    capabilities: Cell<Capabilities>,
+    /// Arguments handed to the program, as returned by `args()`.
+    arguments: RefCell<Vec<String>>,
+    /// Whether top-level declarations may replace existing globals.
+    redefinable: Cell<bool>,
+    call_depth: Cell<usize>,
+    steps: Cell<u64>,
+    deadline: Cell<Option<Instant>>,
+    heap: Heap,
+    /// Class of the values runtime errors are caught as.
+    error_class: Rc<RuntimeValue>,
+    /// Natives and libraries defined by the host, available to every module unless
+    /// one of its globals shadows them.
+    natives: RefCell<HashMap<String, Rc<RuntimeValue>>>,
+    modules: ModuleLoader,
+    /// Modules already executed, by canonical path.
+    loaded: RefCell<HashMap<PathBuf, Rc<RuntimeValue>>>,
+    /// Modules whose top level is still running, from the outermost import.
+    loading: RefCell<Vec<PathBuf>>,
+}
+
+/// Stack space kept free before evaluation continues on a freshly allocated segment.
+const STACK_RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT_SIZE: usize = 2 * 1024 * 1024;
+
+/// Steps taken between two looks at the clock.
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+impl fmt::Debug for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Runtime")
+            .field("globals", &self.globals)
+            .field("environment", &self.environment)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // cycles running through the globals would otherwise outlive the runtime
+        drop(self.globals.clear());
+        self.loaded.borrow_mut().clear();
+        self.heap.collect();
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Runtime {
+    /// Runtime printing to stdout and reading from stdin.
     pub fn new() -> Self {
-        Self {
-            environment: RefCell::new(Rc::new(Environment::new())),
+        Self::with_io(io::stdout(), BufReader::new(io::stdin()))
+    }
+
+    pub fn with_output(output: impl Write + 'static) -> Self {
+        Self::with_io(output, BufReader::new(io::stdin()))
+    }
+
+    pub fn with_io(output: impl Write + 'static, input: impl BufRead + 'static) -> Self {
+        let globals = Rc::new(Environment::new());
+
+        let runtime = Self {
+            environment: RefCell::new(Rc::clone(&globals)),
+            globals,
+            output: RefCell::new(Box::new(output)),
+            input: RefCell::new(Box::new(input)),
+            limits: Cell::new(Limits::default()),
+            capabilities: Cell::new(Capabilities::default()),
+            arguments: RefCell::new(Vec::new()),
+            redefinable: Cell::new(false),
+            call_depth: Cell::new(0),
+            steps: Cell::new(0),
+            deadline: Cell::new(None),
+            heap: Heap::new(),
+            error_class: Rc::new(RuntimeValue::class(
+                "Error".to_string(),
+                None,
+                HashMap::new(),
+            )),
+            natives: RefCell::new(HashMap::new()),
+            modules: ModuleLoader::default(),
+            loaded: RefCell::new(HashMap::new()),
+            loading: RefCell::new(Vec::new()),
+        };
+
+        runtime.heap.track_environment(&runtime.globals);
+
+        for builtin in native::core() {
+            runtime.define_native(builtin);
+        }
+
+        for library in native::libraries() {
+            runtime.define_library(library);
+        }
+
+        runtime
+    }
+
+    /// Makes `function` available to Lox code under its own name. Programs may
+    /// still declare a global with that name, which then shadows the native.
+    pub fn define_native(&self, function: impl NativeFunction + 'static) {
+        let identifier = function.name().to_string();
+        let function = Rc::new(RuntimeValue::native(function));
+
+        self.natives.borrow_mut().insert(identifier, function);
+    }
+
+    /// Makes the members of `library` available to Lox code through a name of its
+    /// own, which globals shadow like the names of natives.
+    pub fn define_library(&self, library: Library) {
+        let identifier = library.name().to_string();
+
+        let globals = Environment::new();
+        let exports = library
+            .into_members()
+            .into_iter()
+            .map(|(name, value)| {
+                globals.define(name.clone(), Rc::new(value)).unwrap();
+                name
+            })
+            .collect();
+
+        let module = Rc::new(RuntimeValue::module(
+            identifier.clone(),
+            Rc::new(globals),
+            exports,
+        ));
+
+        self.natives.borrow_mut().insert(identifier, module);
+    }
+
+    /// Locates the files imported by the program.
+    pub fn modules(&self) -> &ModuleLoader {
+        &self.modules
+    }
+
+    /// Value of the global `identifier`, or of the native or library it names.
+    pub fn get_global(&self, identifier: &str) -> Option<Rc<RuntimeValue>> {
+        self.globals
+            .get(identifier)
+            .or_else(|| self.native(identifier))
+    }
+
+    fn native(&self, identifier: &str) -> Option<Rc<RuntimeValue>> {
+        self.natives.borrow().get(identifier).map(Rc::clone)
+    }
+
+    /// Defines the global `identifier`, overwriting its value if it already exists.
+    pub fn set_global(&self, identifier: &str, value: Rc<RuntimeValue>) {
+        if self.globals.get(identifier).is_some() {
+            self.globals.assign(identifier.to_string(), value).unwrap();
+        } else {
+            self.globals.define(identifier.to_string(), value).unwrap();
         }
     }
 
+    pub fn limits(&self) -> Limits {
+        self.limits.get()
+    }
+
+    /// Replaces the limits and starts counting steps and time from now.
+    pub fn set_limits(&self, limits: Limits) {
+        self.limits.set(limits);
+        self.reset_usage();
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.get()
+    }
+
+    /// Replaces the capabilities granted to the natives reaching outside the runtime.
+    pub fn set_capabilities(&self, capabilities: Capabilities) {
+        self.capabilities.set(capabilities);
+    }
+
+    /// Fails with `PermissionDenied` unless `capability`, needed by the native
+    /// `function`, was granted.
+    pub fn require(&self, capability: Capability, function: &str) -> RuntimeResult<()> {
+        if self.capabilities.get().allows(capability) {
+            Ok(())
+        } else {
+            Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::PermissionDenied(function.to_string(), capability),
+            )))
+        }
+    }
+
+    /// Canonical path of the module `specifier` imported from the file `importer`,
+    /// which takes the `fs` capability outside the directories the host exposed.
+    pub fn resolve_import(
+        &self,
+        specifier: &str,
+        importer: Option<&Path>,
+    ) -> RuntimeResult<PathBuf> {
+        let fs = self.capabilities.get().allows(Capability::Fs);
+
+        self.modules.resolve(specifier, importer, fs)
+    }
+
+    pub fn arguments(&self) -> Vec<String> {
+        self.arguments.borrow().clone()
+    }
+
+    /// Sets the arguments the program receives from `args()`.
+    pub fn set_arguments(&self, arguments: Vec<String>) {
+        self.arguments.replace(arguments);
+    }
+
+    /// Lets top-level declarations replace globals that already exist instead of
+    /// failing, as expected when a session runs one input after another.
+    pub fn set_redefinable_globals(&self, redefinable: bool) {
+        self.redefinable.set(redefinable);
+    }
+
+    /// Restarts the step budget and the timeout, e.g. before running the next script.
+    pub fn reset_usage(&self) {
+        self.steps.set(0);
+        self.deadline.set(
+            self.limits
+                .get()
+                .timeout
+                .map(|timeout| Instant::now() + timeout),
+        );
+    }
+
+    /// Accounts for one evaluation step, failing once the budget or the time runs out.
+    pub fn tick(&self) -> RuntimeResult<()> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+
+        if let Some(max_steps) = self.limits.get().max_steps {
+            if steps > max_steps {
+                return Err(InterpreterError::new(RuntimeError::new(
+                    RuntimeErrorKind::BudgetExhausted(max_steps),
+                )));
+            }
+        }
+
+        if steps.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            if let Some(deadline) = self.deadline.get() {
+                if Instant::now() >= deadline {
+                    return Err(InterpreterError::new(RuntimeError::new(
+                        RuntimeErrorKind::Timeout,
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Approximate number of bytes held by the values and environments still in use.
+    /// Values created by the VM are not counted.
+    pub fn memory_usage(&self) -> usize {
+        self.heap.sweep()
+    }
+
+    /// Runs the cycle collector now, returning the number of objects it freed.
+    /// Collections also happen on their own as the heap grows.
+    pub fn collect_garbage(&self) -> usize {
+        self.heap.collect()
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.heap.stats()
+    }
+
+    /// Moves `value` to the heap, failing if that exceeds the memory limit.
+    pub fn allocate(&self, value: RuntimeValue) -> RuntimeResult<Rc<RuntimeValue>> {
+        let value = Rc::new(value);
+
+        self.heap.track_value(&value);
+        self.check_memory()?;
+
+        Ok(value)
+    }
+
+    fn allocate_environment(&self, environment: Environment) -> RuntimeResult<Rc<Environment>> {
+        let environment = Rc::new(environment);
+
+        self.heap.track_environment(&environment);
+        self.check_memory()?;
+
+        Ok(environment)
+    }
+
+    /// Accounts for an object already on the heap growing by `bytes`, failing if that
+    /// exceeds the memory limit. The charge only lasts until the next collection
+    /// recounts the object, so check [`Runtime::ensure_available`] before building
+    /// anything large.
+    pub fn charge(&self, bytes: usize) -> RuntimeResult<()> {
+        self.heap.charge(bytes);
+        self.check_memory()
+    }
+
+    /// Fails if `bytes` more than the objects in use would exceed the memory limit,
+    /// without accounting for them.
+    pub fn ensure_available(&self, bytes: usize) -> RuntimeResult<()> {
+        let Some(max_memory) = self.limits.get().max_memory else {
+            return Ok(());
+        };
+
+        let exceeds = || self.heap.allocated().saturating_add(bytes) > max_memory;
+
+        // the running estimate also counts objects already dropped, so collect before failing
+        if exceeds() && {
+            self.heap.collect();
+            exceeds()
+        } {
+            return Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::OutOfMemory(max_memory),
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn check_memory(&self) -> RuntimeResult<()> {
+        self.ensure_available(0)
+    }
+
+    /// Writes `value` followed by a newline to the runtime's output.
+    pub fn write_line(&self, value: impl fmt::Display) -> RuntimeResult<()> {
+        let mut output = self.output.borrow_mut();
+
+        writeln!(output, "{value}")
+            .and_then(|_| output.flush())
+            .map_err(|error| InterpreterError::new(RuntimeError::new(RuntimeErrorKind::Io(error))))
+    }
+
+    /// Reads the next line from the runtime's input without its line ending,
+    /// or `None` once the input is exhausted.
+    pub fn read_line(&self) -> RuntimeResult<Option<String>> {
+        let mut line = String::new();
+
+        let read = self
+            .input
+            .borrow_mut()
+            .read_line(&mut line)
+            .map_err(|error| {
+                InterpreterError::new(RuntimeError::new(RuntimeErrorKind::Io(error)))
+            })?;
+
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let trimmed = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(trimmed);
+
+        Ok(Some(line))
+    }
+
     fn environment(&self) -> Rc<Environment> {
         Rc::clone(&self.environment.borrow())
     }
 }
 
 impl Runtime {
+    /// Executes a program whose variables have been annotated by the resolver.
     pub fn run(&self, program: &Vec<Rc<Statement>>) -> RuntimeResult<()> {
         if let Some(signal) = self._run(program)? {
-            Err(InterpreterError::new(RuntimeError::new(match signal {
-                RuntimeSignal::LoopBreak => RuntimeErrorKind::BreakNotWithinLoop,
-                RuntimeSignal::LoopContinue => RuntimeErrorKind::ContinueNotWithinLoop,
-                RuntimeSignal::FunctionReturn(_) => RuntimeErrorKind::ReturnNotWithinFunction,
-            })))
+            Err(InterpreterError::new(self.unhandled(signal, None)))
         } else {
             Ok(())
         }
     }
 
+    /// Error for a signal that reached the top level of the program or of the module `file`.
+    fn unhandled(&self, signal: RuntimeSignal, file: Option<&Rc<ModuleFile>>) -> RuntimeError {
+        match signal {
+            RuntimeSignal::LoopBreak => RuntimeError::new(RuntimeErrorKind::BreakNotWithinLoop),
+            RuntimeSignal::LoopContinue => {
+                RuntimeError::new(RuntimeErrorKind::ContinueNotWithinLoop)
+            }
+            RuntimeSignal::FunctionReturn(_) => {
+                RuntimeError::new(RuntimeErrorKind::ReturnNotWithinFunction)
+            }
+            RuntimeSignal::Throw(value, span) => self.uncaught(value, span, file),
+        }
+    }
+
     fn _run(&self, program: &Vec<Rc<Statement>>) -> RuntimeResult<Option<RuntimeSignal>> {
         for stmt in program {
             if let Some(signal) = self.statement(stmt)? {
@@ -48,33 +437,130 @@ impl Runtime {
     }
 
     fn statement(&self, stmt: &Statement) -> RuntimeResult<Option<RuntimeSignal>> {
-        match stmt {
-            Statement::Expression(expr) => self.expr_stmt(&expr).map(|_| None),
-            Statement::Print(expr) => self.print_stmt(&expr).map(|_| None),
-            Statement::VariableDeclaration {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.tick()?;
+            self.execute(stmt)
+        })
+        .map_err(|mut error| {
+            let file = self.environment().root().file().cloned();
+            error.source_mut().locate_in(stmt.span, file.as_ref());
+            error
+        })
+    }
+
+    fn execute(&self, stmt: &Statement) -> RuntimeResult<Option<RuntimeSignal>> {
+        match &stmt.kind {
+            StatementKind::Expression(expr) => self.expr_stmt(expr).map(|_| None),
+            StatementKind::Print(expr) => self.print_stmt(expr).map(|_| None),
+            StatementKind::VariableDeclaration {
                 identifier,
                 expression,
             } => self
-                .var_stmt(identifier.to_string(), &expression)
+                .var_stmt(identifier.to_string(), expression)
                 .map(|_| None),
-            Statement::FunctionDeclaration {
+            StatementKind::FunctionDeclaration {
                 identifier,
                 parameters,
                 execute,
             } => self.fun_stmt(identifier.to_string(), parameters.clone(), execute),
-            Statement::Block(statements) => self.block(statements),
-            Statement::Conditional {
+            StatementKind::ClassDeclaration {
+                identifier,
+                superclass,
+                methods,
+            } => self.class_stmt(identifier.to_string(), superclass.as_ref(), methods),
+            StatementKind::Block(statements) => self.block(statements),
+            StatementKind::Conditional {
                 condition,
                 then,
                 alternative,
-            } => self.conditional_stmt(condition, then, alternative.as_ref()),
-            Statement::While { condition, block } => self.loop_stmt(condition, block),
-            Statement::Break => Ok(Some(RuntimeSignal::LoopBreak)),
-            Statement::Continue => Ok(Some(RuntimeSignal::LoopContinue)),
-            Statement::Return(expression) => Ok(Some(RuntimeSignal::FunctionReturn(
+            } => self.conditional_stmt(condition, then, alternative.as_deref()),
+            StatementKind::While { condition, block } => self.loop_stmt(condition, block),
+            StatementKind::For {
+                initializer,
+                condition,
+                increment,
+                block,
+            } => self.for_stmt(
+                initializer.as_deref(),
+                condition.as_ref(),
+                increment.as_ref(),
+                block,
+            ),
+            StatementKind::Break => Ok(Some(RuntimeSignal::LoopBreak)),
+            StatementKind::Continue => Ok(Some(RuntimeSignal::LoopContinue)),
+            StatementKind::Return(expression) => Ok(Some(RuntimeSignal::FunctionReturn(
+                self.evaluate(expression)?,
+            ))),
+            StatementKind::Throw(expression) => Ok(Some(RuntimeSignal::Throw(
                 self.evaluate(expression)?,
+                stmt.span,
             ))),
+            StatementKind::Try {
+                block,
+                catch,
+                finally,
+            } => self.try_stmt(
+                block,
+                catch
+                    .as_ref()
+                    .map(|(identifier, handler)| (identifier.as_str(), handler.as_ref())),
+                finally.as_deref(),
+            ),
+            StatementKind::Import { path, alias } => {
+                let module = self.import(path)?;
+                self.declare(alias.to_string(), module)?;
+
+                Ok(None)
+            }
+            StatementKind::ImportFrom { path, names } => {
+                let module = self.import(path)?;
+
+                for name in names {
+                    self.declare(name.to_string(), module::export(&module, name)?)?;
+                }
+
+                Ok(None)
+            }
+            StatementKind::Export(declaration) => self.execute(declaration),
+        }
+    }
+
+    /// Module imported as `specifier` from the code being run, executing it on first use.
+    fn import(&self, specifier: &str) -> RuntimeResult<Rc<RuntimeValue>> {
+        let importer = self.environment().root();
+        let path =
+            self.resolve_import(specifier, importer.file().map(|file| file.path.as_path()))?;
+
+        if let Some(module) = self.loaded.borrow().get(&path) {
+            return Ok(Rc::clone(module));
+        }
+
+        {
+            let loading = self.loading.borrow();
+            let loading = loading.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+            self.modules.check_cycle(&loading, &path)?;
+        }
+
+        let (file, program) = module::load(&path)?;
+
+        let globals = self.allocate_environment(Environment::module(Rc::clone(&file)))?;
+
+        self.loading.borrow_mut().push(path.clone());
+        let signal = self.with_environment(Rc::clone(&globals), || self._run(&program));
+        self.loading.borrow_mut().pop();
+
+        if let Some(signal) = signal? {
+            return Err(InterpreterError::new(self.unhandled(signal, Some(&file))));
         }
+
+        let module = self.allocate(RuntimeValue::module(
+            module::name(&path),
+            globals,
+            module::exports(&program),
+        ))?;
+        self.loaded.borrow_mut().insert(path, Rc::clone(&module));
+
+        Ok(module)
     }
 
     fn fun_stmt(
@@ -85,13 +571,70 @@ impl Runtime {
     ) -> RuntimeResult<Option<RuntimeSignal>> {
         let closure = Rc::clone(&self.environment.borrow());
 
-        let execute = match execute {
-            Statement::Block(statements) => statements,
+        let execute = match &execute.kind {
+            StatementKind::Block(statements) => statements,
             _ => unreachable!(),
         };
 
         let function = RuntimeValue::callable(parameters, execute.to_vec(), closure);
-        self.environment().define(identifier, Rc::new(function))?;
+        self.declare(identifier, self.allocate(function)?)?;
+
+        Ok(None)
+    }
+
+    fn class_stmt(
+        &self,
+        identifier: String,
+        superclass: Option<&Expression>,
+        methods: &[Rc<Statement>],
+    ) -> RuntimeResult<Option<RuntimeSignal>> {
+        let superclass = superclass
+            .map(|superclass| self.evaluate(superclass))
+            .transpose()?;
+
+        let closure = match superclass {
+            Some(ref superclass) => {
+                if !matches!(superclass.as_ref(), RuntimeValue::Class { .. }) {
+                    return Err(InterpreterError::new(RuntimeError::new(
+                        RuntimeErrorKind::SuperclassNotClass,
+                    )));
+                }
+
+                let environment = Environment::with_enclosing(self.environment());
+                environment.define("super".to_string(), Rc::clone(superclass))?;
+                self.allocate_environment(environment)?
+            }
+            None => self.environment(),
+        };
+
+        let methods = methods
+            .iter()
+            .map(|method| match &method.kind {
+                StatementKind::FunctionDeclaration {
+                    identifier,
+                    parameters,
+                    execute,
+                } => {
+                    let execute = match &execute.kind {
+                        StatementKind::Block(statements) => statements,
+                        _ => unreachable!(),
+                    };
+
+                    Ok((
+                        identifier.to_string(),
+                        self.allocate(RuntimeValue::callable(
+                            parameters.clone(),
+                            execute.to_vec(),
+                            Rc::clone(&closure),
+                        ))?,
+                    ))
+                }
+                _ => unreachable!(),
+            })
+            .collect::<RuntimeResult<HashMap<_, _>>>()?;
+
+        let class = RuntimeValue::class(identifier.clone(), superclass, methods);
+        self.declare(identifier, self.allocate(class)?)?;
 
         Ok(None)
     }
@@ -114,16 +657,51 @@ impl Runtime {
         Ok(None)
     }
 
+    fn for_stmt(
+        &self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        block: &Statement,
+    ) -> RuntimeResult<Option<RuntimeSignal>> {
+        let environment =
+            self.allocate_environment(Environment::with_enclosing(self.environment()))?;
+
+        self.with_environment(environment, || {
+            if let Some(initializer) = initializer {
+                self.statement(initializer)?;
+            }
+
+            while match condition {
+                Some(condition) => self.evaluate(condition)?.as_ref().into(),
+                None => true,
+            } {
+                match self.statement(block)? {
+                    Some(RuntimeSignal::LoopBreak) => break,
+                    // the increment clause still runs on `continue`
+                    Some(RuntimeSignal::LoopContinue) | None => (),
+                    other => return Ok(other),
+                }
+
+                if let Some(increment) = increment {
+                    self.evaluate(increment)?;
+                }
+            }
+
+            Ok(None)
+        })
+    }
+
     fn conditional_stmt(
         &self,
         condition: &Expression,
         then: &Statement,
-        alternative: Option<&Box<Statement>>,
+        alternative: Option<&Statement>,
     ) -> RuntimeResult<Option<RuntimeSignal>> {
-        let condition_result = self.evaluate(&condition)?;
+        let condition_result = self.evaluate(condition)?;
 
         // if negated runtime value is false
-        let signal = if !<_ as TryInto<bool>>::try_into(&(!&*condition_result).unwrap()).unwrap() {
+        let signal = if !<_ as Into<bool>>::into(&(!&*condition_result).unwrap()) {
             self.statement(then)?
         } else if let Some(alternative) = alternative {
             self.statement(alternative)?
@@ -134,34 +712,147 @@ impl Runtime {
         Ok(signal)
     }
 
+    fn try_stmt(
+        &self,
+        block: &Statement,
+        catch: Option<(&str, &Statement)>,
+        finally: Option<&Statement>,
+    ) -> RuntimeResult<Option<RuntimeSignal>> {
+        let outcome = self.statement(block);
+
+        let outcome = match catch {
+            Some((identifier, handler)) => match self.catchable(outcome)? {
+                Some(RuntimeSignal::Throw(value, _)) => {
+                    let environment = Environment::with_enclosing(self.environment());
+                    environment.define(identifier.to_string(), value)?;
+
+                    let environment = self.allocate_environment(environment)?;
+                    self.with_environment(environment, || self.statement(handler))
+                }
+                signal => Ok(signal),
+            },
+            // without a handler, errors leave the statement as they came
+            None => outcome,
+        };
+
+        match (&outcome, finally) {
+            (Err(error), _) if !error.source().is_catchable() => outcome,
+            (_, Some(finally)) => match self.statement(finally)? {
+                // a jump out of `finally` discards whatever was pending
+                Some(signal) => Ok(Some(signal)),
+                None => outcome,
+            },
+            (_, None) => outcome,
+        }
+    }
+
+    /// Turns a catchable error into the `Throw` signal of the value it is caught as.
+    fn catchable(
+        &self,
+        result: RuntimeResult<Option<RuntimeSignal>>,
+    ) -> RuntimeResult<Option<RuntimeSignal>> {
+        match result {
+            Err(mut error) if error.source().is_catchable() => {
+                let span = error
+                    .source()
+                    .span()
+                    .expect("errors are located by the statement they occur in");
+
+                let value = match error.source_mut().take_thrown() {
+                    Some(value) => value,
+                    None => self.error_value(error.source())?,
+                };
+
+                Ok(Some(RuntimeSignal::Throw(value, span)))
+            }
+            result => result,
+        }
+    }
+
+    /// Instance of the `Error` class describing `error`, with its `message` and `kind`.
+    fn error_value(&self, error: &RuntimeError) -> RuntimeResult<Rc<RuntimeValue>> {
+        let instance = RuntimeValue::instance(Rc::clone(&self.error_class));
+
+        if let RuntimeValue::Instance { fields, .. } = &instance {
+            let mut fields = fields.borrow_mut();
+            fields.insert(
+                "message".to_string(),
+                self.allocate(RuntimeValue::string(error.to_string()))?,
+            );
+            fields.insert(
+                "kind".to_string(),
+                self.allocate(RuntimeValue::string(error.name().to_string()))?,
+            );
+        }
+
+        self.allocate(instance)
+    }
+
+    /// Error reported for a value thrown at `span` in the module `file` that no `try`
+    /// statement caught.
+    fn uncaught(
+        &self,
+        value: Rc<RuntimeValue>,
+        span: Span,
+        file: Option<&Rc<ModuleFile>>,
+    ) -> RuntimeError {
+        // caught runtime errors keep reading as the original message when thrown again
+        let description = match value.as_ref() {
+            RuntimeValue::Instance { class, fields } if Rc::ptr_eq(class, &self.error_class) => {
+                fields.borrow()["message"].to_string()
+            }
+            value => value.to_string(),
+        };
+
+        let mut error = RuntimeError::uncaught(value, description);
+        error.locate_in(span, file);
+        error
+    }
+
     fn block(&self, statements: &Vec<Rc<Statement>>) -> RuntimeResult<Option<RuntimeSignal>> {
-        let environment = Rc::new(Environment::with_enclosing(Rc::clone(
+        let environment = self.allocate_environment(Environment::with_enclosing(Rc::clone(
             &self.environment.borrow(),
-        )));
-
-        let prev_environment = self.environment.replace(environment);
+        )))?;
 
-        let signal = self._run(statements)?;
+        self.with_environment(environment, || self._run(statements))
+    }
 
+    /// Runs `execute` inside `environment`, restoring the current environment
+    /// afterwards even if it fails.
+    fn with_environment<T>(
+        &self,
+        environment: Rc<Environment>,
+        execute: impl FnOnce() -> RuntimeResult<T>,
+    ) -> RuntimeResult<T> {
+        let prev_environment = self.environment.replace(environment);
+        let result = execute();
         self.environment.replace(prev_environment);
 
-        Ok(signal)
+        result
     }
 
     fn var_stmt(&self, identifier: String, expr: &Expression) -> RuntimeResult<()> {
         let value = self.evaluate(expr)?;
 
-        self.environment().define(identifier, value)?;
+        self.declare(identifier, value)
+    }
 
-        Ok(())
+    /// Binds a declared variable, function or class in the current environment.
+    fn declare(&self, identifier: String, value: Rc<RuntimeValue>) -> RuntimeResult<()> {
+        let environment = self.environment();
+
+        if self.redefinable.get() && Rc::ptr_eq(&environment, &self.globals) {
+            self.set_global(&identifier, value);
+            return Ok(());
+        }
+
+        environment.define(identifier, value)
     }
 
     fn print_stmt(&self, expr: &Expression) -> RuntimeResult<()> {
         let value = self.evaluate(expr)?;
 
-        println!("{value}");
-
-        Ok(())
+        self.write_line(value)
     }
 
     fn expr_stmt(&self, expr: &Expression) -> RuntimeResult<()> {
@@ -170,49 +861,435 @@ impl Runtime {
         Ok(())
     }
 
-    fn evaluate(&self, expr: &Expression) -> RuntimeResult<Rc<RuntimeValue>> {
-        match expr {
-            Expression::Binary {
+    /// Evaluates a resolved expression in the current environment.
+    pub fn evaluate(&self, expr: &Expression) -> RuntimeResult<Rc<RuntimeValue>> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.tick()?;
+            self.evaluate_kind(&expr.kind)
+        })
+        .map_err(|mut error| {
+            let file = self.environment().root().file().cloned();
+            error.source_mut().locate_in(expr.span, file.as_ref());
+            error
+        })
+    }
+
+    fn evaluate_kind(&self, kind: &ExpressionKind) -> RuntimeResult<Rc<RuntimeValue>> {
+        match kind {
+            ExpressionKind::Binary {
                 left,
                 operator,
                 right,
-            } => self.binary(&left, operator, &right),
-            Expression::Unary { operator, right } => self.unary(operator, right),
-            Expression::Literal(literal) => self.literal(literal),
-            Expression::Conditional {
+            } => self.binary(left, operator, right),
+            ExpressionKind::Unary { operator, right } => self.unary(operator, right),
+            ExpressionKind::Literal(literal) => self.literal(literal),
+            ExpressionKind::Conditional {
                 condition,
                 then,
                 alternative,
             } => self.conditional(condition, then, alternative),
-            Expression::Grouping(expr) => self.grouping(expr),
-            Expression::Identifier(identifier) => {
-                self.environment()
-                    .get(identifier)
-                    .ok_or(InterpreterError::new(RuntimeError::new(
-                        RuntimeErrorKind::VariableNotDefined(identifier.to_string()),
-                    )))
-            }
-            Expression::Assignment {
+            ExpressionKind::Grouping(expr) => self.grouping(expr),
+            ExpressionKind::Identifier {
+                identifier,
+                resolution,
+            } => self.lookup_variable(identifier, resolution.get()),
+            ExpressionKind::Assignment {
                 identifier,
                 expression,
-            } => self
-                .environment()
-                .assign(identifier.to_string(), self.evaluate(&expression)?)
-                .map(|_| Rc::new(RuntimeValue::nil())),
-            Expression::FunctionInvokation { callee, arguments } => {
+                resolution,
+            } => self.assignment(identifier, expression, resolution.get()),
+            ExpressionKind::FunctionInvokation { callee, arguments } => {
                 self.function_invokation(callee, arguments)
             }
+            ExpressionKind::Get { object, property } => self.get(object, property),
+            ExpressionKind::Set {
+                object,
+                property,
+                expression,
+            } => self.set(object, property, expression),
+            ExpressionKind::Super { method, resolution } => {
+                self.super_method(method, resolution.get())
+            }
+            ExpressionKind::This { resolution } => self.lookup_variable("this", resolution.get()),
+            ExpressionKind::Function {
+                parameters,
+                execute,
+            } => self.function(parameters, execute),
+            ExpressionKind::List(elements) => self.list(elements),
+            ExpressionKind::Map(entries) => self.map(entries),
+            ExpressionKind::Interpolation(parts) => self.interpolation(parts),
+            ExpressionKind::Index { object, index } => self.index(object, index),
+            ExpressionKind::SetIndex {
+                object,
+                index,
+                expression,
+            } => self.set_index(object, index, expression),
+            ExpressionKind::Slice { object, start, end } => {
+                self.slice(object, start.as_deref(), end.as_deref())
+            }
         }
     }
 
+    fn function(
+        &self,
+        parameters: &[String],
+        execute: &Statement,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let execute = match &execute.kind {
+            StatementKind::Block(statements) => statements,
+            _ => unreachable!(),
+        };
+
+        self.allocate(RuntimeValue::callable(
+            parameters.to_vec(),
+            execute.to_vec(),
+            self.environment(),
+        ))
+    }
+
+    fn lookup_variable(
+        &self,
+        identifier: &str,
+        resolution: Option<(usize, usize)>,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        match resolution {
+            Some((depth, slot)) => self.environment().get_at(depth, slot),
+            None => self
+                .environment()
+                .root()
+                .get(identifier)
+                .or_else(|| self.native(identifier)),
+        }
+        .ok_or(InterpreterError::new(RuntimeError::new(
+            RuntimeErrorKind::VariableNotDefined(identifier.to_string()),
+        )))
+    }
+
+    fn assignment(
+        &self,
+        identifier: &str,
+        expression: &Expression,
+        resolution: Option<(usize, usize)>,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let value = self.evaluate(expression)?;
+
+        match resolution {
+            Some((depth, slot)) => {
+                if self.environment().assign_at(depth, slot, value) {
+                    Ok(())
+                } else {
+                    Err(InterpreterError::new(RuntimeError::new(
+                        RuntimeErrorKind::VariableNotDefined(identifier.to_string()),
+                    )))
+                }
+            }
+            None => {
+                let globals = self.environment().root();
+
+                // assigning to a native gives the module a global of that name
+                if globals.get(identifier).is_none() && self.native(identifier).is_some() {
+                    globals.define(identifier.to_string(), value)
+                } else {
+                    globals.assign(identifier.to_string(), value)
+                }
+            }
+        }
+        .map(|_| Rc::new(RuntimeValue::nil()))
+    }
+
+    fn list(&self, elements: &[Expression]) -> RuntimeResult<Rc<RuntimeValue>> {
+        let values = elements
+            .iter()
+            .map(|element| self.evaluate(element))
+            .collect::<RuntimeResult<Vec<_>>>()?;
+
+        self.allocate(RuntimeValue::list(values))
+    }
+
+    fn interpolation(&self, parts: &[Expression]) -> RuntimeResult<Rc<RuntimeValue>> {
+        let mut value = String::new();
+
+        for part in parts {
+            value.push_str(&self.evaluate(part)?.to_string());
+        }
+
+        self.allocate(RuntimeValue::string(value))
+    }
+
+    fn map(&self, entries: &[(Expression, Expression)]) -> RuntimeResult<Rc<RuntimeValue>> {
+        let evaluated = entries
+            .iter()
+            .map(|(key, value)| Ok((self.evaluate(key)?, self.evaluate(value)?)))
+            .collect::<RuntimeResult<Vec<_>>>()?;
+
+        let mut entries = Entries::new();
+        for (key, value) in evaluated {
+            map::insert(&mut entries, Self::map_key(&key)?, key, value);
+        }
+
+        self.allocate(RuntimeValue::map(entries))
+    }
+
+    fn index(&self, object: &Expression, index: &Expression) -> RuntimeResult<Rc<RuntimeValue>> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+
+        match object.as_ref() {
+            RuntimeValue::List(values) => {
+                let index = Self::integer_index(&index)?;
+                let values = values.borrow();
+
+                list::position(index, values.len())
+                    .map(|position| Rc::clone(&values[position]))
+                    .ok_or_else(|| list::out_of_bounds(index, values.len()))
+            }
+            RuntimeValue::Map(entries) => {
+                let key = Self::map_key(&index)?;
+
+                entries
+                    .borrow()
+                    .get(&key)
+                    .map(|(_, value)| Rc::clone(value))
+                    .ok_or_else(|| map::missing_key(&key))
+            }
+            _ => Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::ExpressionNotIndexable,
+            ))),
+        }
+    }
+
+    fn set_index(
+        &self,
+        object: &Expression,
+        index: &Expression,
+        expression: &Expression,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+        let value = self.evaluate(expression)?;
+
+        match object.as_ref() {
+            RuntimeValue::List(values) => {
+                let index = Self::integer_index(&index)?;
+                let mut values = values.borrow_mut();
+                let len = values.len();
+
+                let position =
+                    list::position(index, len).ok_or_else(|| list::out_of_bounds(index, len))?;
+                values[position] = value;
+            }
+            RuntimeValue::Map(entries) => {
+                let key = Self::map_key(&index)?;
+                let size = key.entry_size::<Rc<RuntimeValue>>();
+
+                if map::insert(&mut entries.borrow_mut(), key, index, value) {
+                    self.charge(size)?;
+                }
+            }
+            _ => {
+                return Err(InterpreterError::new(RuntimeError::new(
+                    RuntimeErrorKind::ExpressionNotIndexable,
+                )))
+            }
+        }
+
+        Ok(Rc::new(RuntimeValue::nil()))
+    }
+
+    fn slice(
+        &self,
+        object: &Expression,
+        start: Option<&Expression>,
+        end: Option<&Expression>,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let object = self.evaluate(object)?;
+
+        // a missing bound and an explicit `nil` both leave the slice open on that side
+        let bound = |bound: Option<&Expression>| {
+            bound
+                .map(|bound| self.evaluate(bound))
+                .transpose()?
+                .filter(|bound| !matches!(bound.as_ref(), RuntimeValue::Nil))
+                .map(|bound| Self::integer_index(&bound))
+                .transpose()
+        };
+        let start = bound(start)?;
+        let end = bound(end)?;
+
+        let RuntimeValue::List(values) = object.as_ref() else {
+            return Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::ExpressionNotSliceable,
+            )));
+        };
+
+        let values = values.borrow();
+        let selected = values[list::slice(start, end, values.len())].to_vec();
+
+        self.allocate(RuntimeValue::list(selected))
+    }
+
+    fn integer_index(index: &RuntimeValue) -> RuntimeResult<i64> {
+        match index {
+            RuntimeValue::Integer(index) => Ok(*index),
+            _ => Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::IndexNotInteger,
+            ))),
+        }
+    }
+
+    fn map_key(key: &RuntimeValue) -> RuntimeResult<MapKey> {
+        key.key().ok_or_else(map::invalid_key)
+    }
+
+    fn get(&self, object: &Expression, property: &str) -> RuntimeResult<Rc<RuntimeValue>> {
+        let object = self.evaluate(object)?;
+
+        if let RuntimeValue::List(_) = object.as_ref() {
+            let method = ListMethod::from_name(property).ok_or(InterpreterError::new(
+                RuntimeError::new(RuntimeErrorKind::UndefinedProperty(property.to_string())),
+            ))?;
+
+            return self.allocate(RuntimeValue::native(BoundListMethod::new(
+                Rc::clone(&object),
+                method,
+            )));
+        }
+
+        if let RuntimeValue::String(_) = object.as_ref() {
+            let method = StringMethod::from_name(property).ok_or(InterpreterError::new(
+                RuntimeError::new(RuntimeErrorKind::UndefinedProperty(property.to_string())),
+            ))?;
+
+            return self.allocate(RuntimeValue::native(BoundStringMethod::new(
+                Rc::clone(&object),
+                method,
+            )));
+        }
+
+        if let RuntimeValue::Module { .. } = object.as_ref() {
+            return module::export(&object, property);
+        }
+
+        if let RuntimeValue::Map(_) = object.as_ref() {
+            let method = MapMethod::from_name(property).ok_or(InterpreterError::new(
+                RuntimeError::new(RuntimeErrorKind::UndefinedProperty(property.to_string())),
+            ))?;
+
+            return self.allocate(RuntimeValue::native(BoundMapMethod::new(
+                Rc::clone(&object),
+                method,
+            )));
+        }
+
+        let RuntimeValue::Instance { class, fields } = object.as_ref() else {
+            return Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::ExpressionNotInstance,
+            )));
+        };
+
+        if let Some(value) = fields.borrow().get(property) {
+            return Ok(Rc::clone(value));
+        }
+
+        class
+            .find_method(property)
+            .map(|method| method.bind(Rc::clone(&object), &self.heap))
+            .transpose()?
+            .flatten()
+            .map(|method| self.allocate(method))
+            .transpose()?
+            .ok_or(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::UndefinedProperty(property.to_string()),
+            )))
+    }
+
+    fn super_method(
+        &self,
+        method: &str,
+        resolution: Option<(usize, usize)>,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let superclass = self.lookup_variable("super", resolution)?;
+        // `this` is always bound in the environment right inside the one holding `super`
+        let instance = self.lookup_variable(
+            "this",
+            resolution.map(|(depth, _)| (depth.saturating_sub(1), 0)),
+        )?;
+
+        superclass
+            .find_method(method)
+            .map(|method| method.bind(instance, &self.heap))
+            .transpose()?
+            .flatten()
+            .map(|method| self.allocate(method))
+            .transpose()?
+            .ok_or(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::UndefinedProperty(method.to_string()),
+            )))
+    }
+
+    fn set(
+        &self,
+        object: &Expression,
+        property: &str,
+        expression: &Expression,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let object = self.evaluate(object)?;
+
+        let RuntimeValue::Instance { fields, .. } = object.as_ref() else {
+            return Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::ExpressionNotInstance,
+            )));
+        };
+
+        let value = self.evaluate(expression)?;
+        fields.borrow_mut().insert(property.to_string(), value);
+
+        Ok(Rc::new(RuntimeValue::nil()))
+    }
+
     fn function_invokation(
         &self,
         callee: &Expression,
-        arguments: &Vec<Expression>,
+        arguments: &[Expression],
     ) -> RuntimeResult<Rc<RuntimeValue>> {
-        let callee_expr = self.evaluate(callee)?;
+        let callee = self.evaluate(callee)?;
+
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<RuntimeResult<Vec<_>>>()?;
+
+        self.call(&callee, arguments)
+    }
 
-        match callee_expr.as_ref() {
+    /// Calls a function, class or native with already evaluated arguments.
+    pub fn call(
+        &self,
+        callee: &Rc<RuntimeValue>,
+        arguments: Vec<Rc<RuntimeValue>>,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let depth = self.call_depth.get();
+        let max_call_depth = self.limits.get().max_call_depth;
+
+        if depth >= max_call_depth {
+            return Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::StackOverflow(max_call_depth),
+            )));
+        }
+
+        self.call_depth.set(depth + 1);
+        let result = self.dispatch_call(callee, arguments);
+        self.call_depth.set(depth);
+
+        result
+    }
+
+    fn dispatch_call(
+        &self,
+        callee: &Rc<RuntimeValue>,
+        arguments: Vec<Rc<RuntimeValue>>,
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        match callee.as_ref() {
             RuntimeValue::Callable {
                 execute,
                 closure,
@@ -225,17 +1302,12 @@ impl Runtime {
                 }
 
                 let environment = Environment::with_enclosing(Rc::clone(closure));
-                for (idx, argument) in arguments.iter().enumerate() {
-                    let name = parameters[idx].to_string();
-                    let argument_value = self.evaluate(argument)?;
-                    environment.define(name, argument_value)?;
+                for (name, argument_value) in parameters.iter().zip(arguments) {
+                    environment.define(name.to_string(), argument_value)?;
                 }
 
-                let prev_environment = self.environment.replace(Rc::new(environment));
-
-                let signal = self.block(execute)?;
-
-                self.environment.replace(prev_environment);
+                let environment = self.allocate_environment(environment)?;
+                let signal = self.with_environment(environment, || self.block(execute))?;
 
                 let return_value = if let Some(signal) = signal {
                     match signal {
@@ -250,6 +1322,13 @@ impl Runtime {
                                 RuntimeErrorKind::ContinueNotWithinLoop,
                             )))
                         }
+                        RuntimeSignal::Throw(value, span) => {
+                            return Err(InterpreterError::new(self.uncaught(
+                                value,
+                                span,
+                                closure.root().file(),
+                            )))
+                        }
                     }
                 } else {
                     Rc::new(RuntimeValue::Nil)
@@ -257,6 +1336,34 @@ impl Runtime {
 
                 Ok(return_value)
             }
+            RuntimeValue::Class { .. } => {
+                let instance = self.allocate(RuntimeValue::instance(Rc::clone(callee)))?;
+
+                match callee.find_method("init") {
+                    Some(initializer) => {
+                        let initializer =
+                            initializer.bind(Rc::clone(&instance), &self.heap)?.unwrap();
+                        self.call(&self.allocate(initializer)?, arguments)?;
+                    }
+                    None if !arguments.is_empty() => {
+                        return Err(InterpreterError::new(RuntimeError::new(
+                            RuntimeErrorKind::InvalidArgumentCount(arguments.len(), 0),
+                        )))
+                    }
+                    None => (),
+                }
+
+                Ok(instance)
+            }
+            RuntimeValue::Native(function) => {
+                if arguments.len() != function.arity() {
+                    return Err(InterpreterError::new(RuntimeError::new(
+                        RuntimeErrorKind::InvalidArgumentCount(arguments.len(), function.arity()),
+                    )));
+                }
+
+                function.call(self, &arguments)
+            }
             _ => Err(InterpreterError::new(RuntimeError::new(
                 RuntimeErrorKind::ExpressionNotCallable,
             ))),
@@ -264,18 +1371,20 @@ impl Runtime {
     }
 
     fn literal(&self, literal: &Literal) -> RuntimeResult<Rc<RuntimeValue>> {
-        Ok(Rc::new(match literal {
+        self.allocate(match literal {
             Literal::Boolean(value) => RuntimeValue::Boolean(*value),
             Literal::String(value) => RuntimeValue::String(value.clone()),
+            Literal::Integer(value) => RuntimeValue::Integer(*value),
+            // literals beyond the integer range stay floats rather than saturating
             Literal::Number(value) => {
-                if value.fract() == 0.0 {
+                if value.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(value) {
                     RuntimeValue::Integer(*value as i64)
                 } else {
                     RuntimeValue::Float(*value)
                 }
             }
             Literal::Nil => RuntimeValue::Nil,
-        }))
+        })
     }
 
     fn grouping(&self, expr: &Expression) -> RuntimeResult<Rc<RuntimeValue>> {
@@ -283,7 +1392,7 @@ impl Runtime {
     }
 
     fn unary(&self, operator: &Operator, expr: &Expression) -> RuntimeResult<Rc<RuntimeValue>> {
-        let right = self.evaluate(&expr)?;
+        let right = self.evaluate(expr)?;
 
         match operator {
             Operator::Subtraction => (-&*right).map(Rc::new),
@@ -302,9 +1411,9 @@ impl Runtime {
         operator: &Operator,
         right_ast: &Expression,
     ) -> RuntimeResult<Rc<RuntimeValue>> {
-        let left = self.evaluate(&left)?;
+        let left = self.evaluate(left)?;
         let right = if *operator != Operator::Conjunction && *operator != Operator::Disjunction {
-            Some(self.evaluate(&right_ast)?)
+            Some(self.evaluate(right_ast)?)
         } else {
             None
         };
@@ -338,10 +1447,10 @@ impl Runtime {
             )),
             _ => unreachable!(),
         }
-        .map(Rc::new)
         .ok_or(InterpreterError::new(RuntimeError::new(
             RuntimeErrorKind::ExpectedNumberOperand,
         )))
+        .and_then(|value| self.allocate(value))
     }
 
     fn conditional(
@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// Access to the host that a native function needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Reading and writing files and directories.
+    Fs,
+    /// Reading environment variables and the program's arguments.
+    Env,
+    /// Reading lines from the runtime's input.
+    Input,
+    /// Ending the program with an exit code.
+    Exit,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fs => write!(f, "fs"),
+            Self::Env => write!(f, "env"),
+            Self::Input => write!(f, "input"),
+            Self::Exit => write!(f, "exit"),
+        }
+    }
+}
+
+/// Capabilities granted to a program. Nothing is granted by default, so hosts
+/// running untrusted code only have to leave them alone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub fs: bool,
+    pub env: bool,
+    pub input: bool,
+    pub exit: bool,
+}
+
+impl Capabilities {
+    /// Every capability, for programs that are trusted like the host itself.
+    pub fn all() -> Self {
+        Self {
+            fs: true,
+            env: true,
+            input: true,
+            exit: true,
+        }
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Fs => self.fs,
+            Capability::Env => self.env,
+            Capability::Input => self.input,
+            Capability::Exit => self.exit,
+        }
+    }
+}
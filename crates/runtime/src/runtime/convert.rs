@@ -0,0 +1,220 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use super::{map::MapKey, value::RuntimeValue};
+
+/// Conversion of host values into Lox values.
+pub trait IntoRuntimeValue {
+    fn into_runtime_value(self) -> RuntimeValue;
+}
+
+/// Conversion of Lox values into host values, failing on a type mismatch.
+pub trait FromRuntimeValue: Sized {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self>;
+}
+
+impl IntoRuntimeValue for RuntimeValue {
+    fn into_runtime_value(self) -> RuntimeValue {
+        self
+    }
+}
+
+impl IntoRuntimeValue for i64 {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::integer(self)
+    }
+}
+
+impl IntoRuntimeValue for i32 {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::integer(self.into())
+    }
+}
+
+impl IntoRuntimeValue for f64 {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::float(self)
+    }
+}
+
+impl IntoRuntimeValue for bool {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::boolean(self)
+    }
+}
+
+impl IntoRuntimeValue for String {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::string(self)
+    }
+}
+
+impl IntoRuntimeValue for &str {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::string(self.to_string())
+    }
+}
+
+impl IntoRuntimeValue for () {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::nil()
+    }
+}
+
+impl<T: IntoRuntimeValue> IntoRuntimeValue for Option<T> {
+    fn into_runtime_value(self) -> RuntimeValue {
+        self.map(T::into_runtime_value)
+            .unwrap_or_else(RuntimeValue::nil)
+    }
+}
+
+impl<T: IntoRuntimeValue> IntoRuntimeValue for Vec<T> {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::list(
+            self.into_iter()
+                .map(|value| Rc::new(value.into_runtime_value()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: IntoRuntimeValue> IntoRuntimeValue for BTreeMap<String, T> {
+    fn into_runtime_value(self) -> RuntimeValue {
+        RuntimeValue::map(
+            self.into_iter()
+                .map(|(key, value)| {
+                    (
+                        MapKey::String(key.clone()),
+                        (
+                            Rc::new(RuntimeValue::string(key)),
+                            Rc::new(value.into_runtime_value()),
+                        ),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl FromRuntimeValue for Rc<RuntimeValue> {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl FromRuntimeValue for i64 {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Integers are widened, as Lox mixes both freely in arithmetic.
+impl FromRuntimeValue for f64 {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::Integer(value) => Some(*value as f64),
+            RuntimeValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromRuntimeValue for bool {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl FromRuntimeValue for String {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromRuntimeValue for () {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        matches!(value.as_ref(), RuntimeValue::Nil).then_some(())
+    }
+}
+
+/// Every element has to convert to `T`.
+impl<T: FromRuntimeValue> FromRuntimeValue for Vec<T> {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::List(values) => values
+                .borrow()
+                .iter()
+                .map(|value| T::from_runtime_value(Rc::clone(value)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+/// Every key has to be a string and every value has to convert to `T`.
+impl<T: FromRuntimeValue> FromRuntimeValue for BTreeMap<String, T> {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::Map(entries) => entries
+                .borrow()
+                .values()
+                .map(|(key, value)| match key.as_ref() {
+                    RuntimeValue::String(key) => {
+                        T::from_runtime_value(Rc::clone(value)).map(|value| (key.clone(), value))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+/// `nil` maps to `None`, anything else has to convert to `T`.
+impl<T: FromRuntimeValue> FromRuntimeValue for Option<T> {
+    fn from_runtime_value(value: Rc<RuntimeValue>) -> Option<Self> {
+        match value.as_ref() {
+            RuntimeValue::Nil => Some(None),
+            _ => T::from_runtime_value(value).map(Some),
+        }
+    }
+}
+
+/// Argument lists for calling Lox functions from the host.
+pub trait IntoArguments {
+    fn into_arguments(self) -> Vec<Rc<RuntimeValue>>;
+}
+
+impl IntoArguments for Vec<Rc<RuntimeValue>> {
+    fn into_arguments(self) -> Vec<Rc<RuntimeValue>> {
+        self
+    }
+}
+
+macro_rules! tuple_arguments {
+    ($($name:ident),*) => {
+        impl<$($name: IntoRuntimeValue),*> IntoArguments for ($($name,)*) {
+            #[allow(non_snake_case)]
+            fn into_arguments(self) -> Vec<Rc<RuntimeValue>> {
+                let ($($name,)*) = self;
+                vec![$(Rc::new($name.into_runtime_value())),*]
+            }
+        }
+    };
+}
+
+tuple_arguments!();
+tuple_arguments!(A);
+tuple_arguments!(A, B);
+tuple_arguments!(A, B, C);
+tuple_arguments!(A, B, C, D);
+tuple_arguments!(A, B, C, D, E);
+tuple_arguments!(A, B, C, D, E, F);
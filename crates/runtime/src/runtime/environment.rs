@@ -1,15 +1,23 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    mem::{self, size_of},
+    rc::Rc,
+};
 
 use error::InterpreterError;
 
 use crate::error::{RuntimeError, RuntimeErrorKind, RuntimeResult};
 
-use super::value::RuntimeValue;
+use super::{module::ModuleFile, value::RuntimeValue};
 
 #[derive(Default, Debug)]
 pub struct Environment {
-    values: RefCell<HashMap<String, Rc<RuntimeValue>>>,
+    values: RefCell<Vec<Rc<RuntimeValue>>>,
+    slots: RefCell<HashMap<String, usize>>,
     enclosing: Option<Rc<Environment>>,
+    /// File of the module whose globals this is.
+    file: Option<Rc<ModuleFile>>,
 }
 
 impl Environment {
@@ -24,40 +32,139 @@ impl Environment {
         }
     }
 
+    /// Global environment of the module read from `file`.
+    pub fn module(file: Rc<ModuleFile>) -> Self {
+        Self {
+            file: Some(file),
+            ..Default::default()
+        }
+    }
+
+    /// File of the module this is the global environment of.
+    pub fn file(&self) -> Option<&Rc<ModuleFile>> {
+        self.file.as_ref()
+    }
+
+    /// Outermost environment, holding the globals of the module the code was written in.
+    pub fn root(self: &Rc<Self>) -> Rc<Self> {
+        let mut environment = self;
+
+        while let Some(enclosing) = &environment.enclosing {
+            environment = enclosing;
+        }
+
+        Rc::clone(environment)
+    }
+
+    /// Approximate number of bytes occupied by the environment's own variables.
+    pub fn size(&self) -> usize {
+        size_of::<Self>()
+            + self
+                .slots
+                .borrow()
+                .keys()
+                .map(|identifier| {
+                    size_of::<String>()
+                        + identifier.capacity()
+                        + size_of::<usize>()
+                        + size_of::<Rc<RuntimeValue>>()
+                })
+                .sum::<usize>()
+    }
+
+    /// Visits the address of every value and of the enclosing environment held by this one.
+    /// Returns `false` if the variables are being mutated and could not be inspected.
+    pub(crate) fn trace(&self, visit: &mut dyn FnMut(*const ())) -> bool {
+        if let Some(enclosing) = &self.enclosing {
+            visit(Rc::as_ptr(enclosing) as *const ());
+        }
+
+        match self.values.try_borrow() {
+            Ok(values) => {
+                values
+                    .iter()
+                    .for_each(|value| visit(Rc::as_ptr(value) as *const ()));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Removes every variable, returning their values.
+    pub(crate) fn clear(&self) -> Vec<Rc<RuntimeValue>> {
+        self.slots.borrow_mut().clear();
+
+        self.values
+            .try_borrow_mut()
+            .map(|mut values| mem::take(&mut *values))
+            .unwrap_or_default()
+    }
+
+    /// Defines a variable in the next free slot of this environment.
     pub fn define(&self, identifier: String, value: Rc<RuntimeValue>) -> RuntimeResult<()> {
-        if self.values.borrow().contains_key(&identifier) {
+        if self.slots.borrow().contains_key(&identifier) {
             Err(InterpreterError::new(RuntimeError::new(
                 RuntimeErrorKind::VariableAlreadyDefined(identifier),
             )))
         } else {
-            self.values.borrow_mut().insert(identifier, value);
+            let mut values = self.values.borrow_mut();
+            self.slots.borrow_mut().insert(identifier, values.len());
+            values.push(value);
             Ok(())
         }
     }
 
     pub fn get(&self, identifier: &str) -> Option<Rc<RuntimeValue>> {
-        let values = self.values.borrow();
-        let value = values.get(identifier).map(Rc::clone);
+        let slot = self.slots.borrow().get(identifier).copied();
 
-        if value.is_none() && self.enclosing.is_some() {
-            self.enclosing.as_ref().unwrap().get(identifier)
-        } else {
-            value
+        match (slot, &self.enclosing) {
+            (Some(slot), _) => self.values.borrow().get(slot).map(Rc::clone),
+            (None, Some(enclosing)) => enclosing.get(identifier),
+            (None, None) => None,
         }
     }
 
     pub fn assign(&self, identifier: String, value: Rc<RuntimeValue>) -> RuntimeResult<()> {
-        if !self.values.borrow().contains_key(&identifier) {
-            if self.enclosing.is_some() {
-                self.enclosing.as_ref().unwrap().assign(identifier, value)
-            } else {
-                Err(InterpreterError::new(RuntimeError::new(
-                    RuntimeErrorKind::VariableNotDefined(identifier),
-                )))
+        let slot = self.slots.borrow().get(&identifier).copied();
+
+        match (slot, &self.enclosing) {
+            (Some(slot), _) => {
+                self.values.borrow_mut()[slot] = value;
+                Ok(())
             }
-        } else {
-            self.values.borrow_mut().insert(identifier, value);
-            Ok(())
+            (None, Some(enclosing)) => enclosing.assign(identifier, value),
+            (None, None) => Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::VariableNotDefined(identifier),
+            ))),
         }
     }
+
+    /// Reads a variable resolved to `slot` of the environment `depth` levels up.
+    pub fn get_at(&self, depth: usize, slot: usize) -> Option<Rc<RuntimeValue>> {
+        self.ancestor(depth)
+            .and_then(|environment| environment.values.borrow().get(slot).map(Rc::clone))
+    }
+
+    /// Overwrites a variable resolved to `slot` of the environment `depth` levels up.
+    pub fn assign_at(&self, depth: usize, slot: usize, value: Rc<RuntimeValue>) -> bool {
+        self.ancestor(depth)
+            .and_then(|environment| {
+                environment
+                    .values
+                    .borrow_mut()
+                    .get_mut(slot)
+                    .map(|current| *current = value)
+            })
+            .is_some()
+    }
+
+    fn ancestor(&self, depth: usize) -> Option<&Environment> {
+        let mut environment = self;
+
+        for _ in 0..depth {
+            environment = environment.enclosing.as_ref()?;
+        }
+
+        Some(environment)
+    }
 }
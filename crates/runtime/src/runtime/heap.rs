@@ -0,0 +1,329 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    mem,
+    rc::{Rc, Weak},
+};
+
+use super::{environment::Environment, value::RuntimeValue};
+
+/// Objects tracked before the first collection.
+const INITIAL_COLLECTION_THRESHOLD: usize = 1024;
+
+/// Bookkeeping of the values and environments allocated by a runtime.
+///
+/// Objects are still freed by reference counting. The heap estimates how much
+/// memory they hold and collects the reference cycles that counting alone can
+/// never free, e.g. a recursive function and the environment it is defined in.
+#[derive(Debug, Default)]
+pub struct Heap {
+    values: RefCell<Vec<Weak<RuntimeValue>>>,
+    environments: RefCell<Vec<Weak<Environment>>>,
+    /// Bytes still in use at the last sweep plus everything allocated since.
+    allocated: Cell<usize>,
+    /// Number of tracked objects that survived the last sweep.
+    survivors: Cell<usize>,
+    collections: Cell<u64>,
+    collected: Cell<u64>,
+}
+
+/// Snapshot of the garbage collector's work, see [`crate::Runtime::gc_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// Number of collections run so far.
+    pub collections: u64,
+    /// Objects freed by breaking unreachable reference cycles.
+    pub collected: u64,
+    /// Values and environments currently tracked.
+    pub objects: usize,
+    /// Estimated bytes held by the tracked objects.
+    pub bytes: usize,
+}
+
+/// A tracked object kept alive for the duration of a collection.
+enum Object {
+    Value(Rc<RuntimeValue>),
+    Environment(Rc<Environment>),
+}
+
+impl Object {
+    fn address(&self) -> *const () {
+        match self {
+            Object::Value(value) => Rc::as_ptr(value) as *const (),
+            Object::Environment(environment) => Rc::as_ptr(environment) as *const (),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Object::Value(value) => Rc::strong_count(value),
+            Object::Environment(environment) => Rc::strong_count(environment),
+        }
+    }
+
+    /// Visits the address of every object this one holds a strong reference to.
+    /// Returns `false` if the object is being mutated and could not be inspected.
+    fn trace(&self, visit: &mut dyn FnMut(*const ())) -> bool {
+        match self {
+            Object::Environment(environment) => environment.trace(visit),
+            Object::Value(value) => match value.as_ref() {
+                RuntimeValue::Callable { closure, .. } => {
+                    visit(Rc::as_ptr(closure) as *const ());
+                    true
+                }
+                RuntimeValue::Module { globals, .. } => {
+                    visit(Rc::as_ptr(globals) as *const ());
+                    true
+                }
+                RuntimeValue::Class {
+                    superclass,
+                    methods,
+                    ..
+                } => {
+                    superclass
+                        .iter()
+                        .chain(methods.values())
+                        .for_each(|value| visit(Rc::as_ptr(value) as *const ()));
+                    true
+                }
+                RuntimeValue::Instance { class, fields } => {
+                    visit(Rc::as_ptr(class) as *const ());
+
+                    match fields.try_borrow() {
+                        Ok(fields) => {
+                            fields
+                                .values()
+                                .for_each(|value| visit(Rc::as_ptr(value) as *const ()));
+                            true
+                        }
+                        Err(_) => false,
+                    }
+                }
+                RuntimeValue::List(values) => match values.try_borrow() {
+                    Ok(values) => {
+                        values
+                            .iter()
+                            .for_each(|value| visit(Rc::as_ptr(value) as *const ()));
+                        true
+                    }
+                    Err(_) => false,
+                },
+                RuntimeValue::Map(entries) => match entries.try_borrow() {
+                    Ok(entries) => {
+                        entries.values().for_each(|(key, value)| {
+                            visit(Rc::as_ptr(key) as *const ());
+                            visit(Rc::as_ptr(value) as *const ());
+                        });
+                        true
+                    }
+                    Err(_) => false,
+                },
+                _ => true,
+            },
+        }
+    }
+
+    /// Drops the references that can form cycles, handing them back so they are
+    /// released only once every garbage object has been cleared.
+    fn clear(&self) -> Vec<Rc<RuntimeValue>> {
+        match self {
+            Object::Environment(environment) => environment.clear(),
+            Object::Value(value) => match value.as_ref() {
+                RuntimeValue::Instance { fields, .. } => fields
+                    .try_borrow_mut()
+                    .map(|mut fields| mem::take(&mut *fields).into_values().collect())
+                    .unwrap_or_default(),
+                RuntimeValue::List(values) => values
+                    .try_borrow_mut()
+                    .map(|mut values| mem::take(&mut *values))
+                    .unwrap_or_default(),
+                RuntimeValue::Map(entries) => entries
+                    .try_borrow_mut()
+                    .map(|mut entries| {
+                        mem::take(&mut *entries)
+                            .into_values()
+                            .flat_map(|(key, value)| [key, value])
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                _ => vec![],
+            },
+        }
+    }
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Estimated number of bytes in use, possibly counting objects dropped since the last sweep.
+    pub fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+
+    pub fn stats(&self) -> GcStats {
+        GcStats {
+            collections: self.collections.get(),
+            collected: self.collected.get(),
+            objects: self.tracked(),
+            bytes: self.allocated(),
+        }
+    }
+
+    /// Starts accounting for `value`. Scalars live inline and are not tracked.
+    pub fn track_value(&self, value: &Rc<RuntimeValue>) {
+        if matches!(
+            value.as_ref(),
+            RuntimeValue::Integer(_)
+                | RuntimeValue::Float(_)
+                | RuntimeValue::Boolean(_)
+                | RuntimeValue::Nil
+        ) {
+            return;
+        }
+
+        self.values.borrow_mut().push(Rc::downgrade(value));
+        self.grow(value.size());
+    }
+
+    /// Accounts for `bytes` more held by an object that is already tracked, e.g. a grown list.
+    pub fn charge(&self, bytes: usize) {
+        self.grow(bytes);
+    }
+
+    pub fn track_environment(&self, environment: &Rc<Environment>) {
+        self.environments
+            .borrow_mut()
+            .push(Rc::downgrade(environment));
+        self.grow(environment.size());
+    }
+
+    /// Forgets every object that has been dropped and recounts the bytes still in use.
+    pub fn sweep(&self) -> usize {
+        let mut allocated = 0;
+
+        self.values
+            .borrow_mut()
+            .retain(|value| match value.upgrade() {
+                Some(value) => {
+                    allocated += value.size();
+                    true
+                }
+                None => false,
+            });
+        self.environments
+            .borrow_mut()
+            .retain(|environment| match environment.upgrade() {
+                Some(environment) => {
+                    allocated += environment.size();
+                    true
+                }
+                None => false,
+            });
+
+        self.survivors.set(self.tracked());
+        self.allocated.set(allocated);
+
+        allocated
+    }
+
+    /// Frees tracked objects that are only reachable through reference cycles
+    /// and returns how many there were.
+    ///
+    /// Roots are found by subtracting the references objects hold to each other
+    /// from their reference counts: whatever is left comes from outside the heap,
+    /// such as the runtime's environment stack or values held by the host.
+    pub fn collect(&self) -> usize {
+        let objects = self
+            .values
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(Object::Value)
+            .chain(
+                self.environments
+                    .borrow()
+                    .iter()
+                    .filter_map(Weak::upgrade)
+                    .map(Object::Environment),
+            )
+            .collect::<Vec<_>>();
+
+        let indices = objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| (object.address(), index))
+            .collect::<HashMap<_, _>>();
+
+        let mut internal = vec![0; objects.len()];
+        let mut reachable = vec![false; objects.len()];
+
+        for (index, object) in objects.iter().enumerate() {
+            let traced = object.trace(&mut |address| {
+                if let Some(&child) = indices.get(&address) {
+                    internal[child] += 1;
+                }
+            });
+
+            // an object that cannot be inspected right now is kept, along with everything it holds
+            if !traced {
+                reachable[index] = true;
+            }
+        }
+
+        // one of the strong references is the upgrade held by `objects`
+        let mut pending = (0..objects.len())
+            .filter(|&index| {
+                reachable[index] || objects[index].strong_count() - 1 > internal[index]
+            })
+            .collect::<Vec<_>>();
+        pending.iter().for_each(|&index| reachable[index] = true);
+
+        while let Some(index) = pending.pop() {
+            objects[index].trace(&mut |address| {
+                if let Some(&child) = indices.get(&address) {
+                    if !reachable[child] {
+                        reachable[child] = true;
+                        pending.push(child);
+                    }
+                }
+            });
+        }
+
+        let garbage = objects
+            .iter()
+            .zip(&reachable)
+            .filter(|(_, reachable)| !**reachable)
+            .map(|(object, _)| object)
+            .collect::<Vec<_>>();
+        let collected = garbage.len();
+
+        let released = garbage
+            .into_iter()
+            .flat_map(Object::clear)
+            .collect::<Vec<_>>();
+
+        drop(released);
+        drop(objects);
+
+        self.collections.set(self.collections.get() + 1);
+        self.collected.set(self.collected.get() + collected as u64);
+        self.sweep();
+
+        collected
+    }
+
+    fn grow(&self, bytes: usize) {
+        self.allocated.set(self.allocated.get() + bytes);
+
+        // collecting once the registry doubles keeps its size proportional to the live objects
+        if self.tracked() >= (2 * self.survivors.get()).max(INITIAL_COLLECTION_THRESHOLD) {
+            self.collect();
+        }
+    }
+
+    fn tracked(&self) -> usize {
+        self.values.borrow().len() + self.environments.borrow().len()
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+/// Bounds on the resources a program may use before it is stopped with an error.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Maximum number of nested calls.
+    pub max_call_depth: usize,
+    /// Maximum number of evaluation steps, counted from the last
+    /// [`crate::Runtime::reset_usage`].
+    pub max_steps: Option<u64>,
+    /// Approximate number of bytes that values and environments may occupy. Only
+    /// the tree-walker tracks memory, so the VM refuses to run with this set.
+    pub max_memory: Option<usize>,
+    /// Wall-clock time allowed, counted from the last [`crate::Runtime::reset_usage`].
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_call_depth: 4096,
+            max_steps: None,
+            max_memory: None,
+            timeout: None,
+        }
+    }
+}
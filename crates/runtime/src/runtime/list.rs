@@ -0,0 +1,232 @@
+use std::{cell::RefCell, fmt, mem::size_of, ops::Range, rc::Rc};
+
+use error::InterpreterError;
+
+use crate::{
+    error::{RuntimeError, RuntimeErrorKind, RuntimeResult},
+    Runtime,
+};
+
+use super::{
+    native::{invalid_argument, NativeFunction},
+    value::RuntimeValue,
+};
+
+/// Methods available on every list, e.g. `xs.push(1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListMethod {
+    Push,
+    Pop,
+    Insert,
+    Remove,
+    Contains,
+    Join,
+}
+
+impl ListMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "push" => Some(Self::Push),
+            "pop" => Some(Self::Pop),
+            "insert" => Some(Self::Insert),
+            "remove" => Some(Self::Remove),
+            "contains" => Some(Self::Contains),
+            "join" => Some(Self::Join),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Push => "push",
+            Self::Pop => "pop",
+            Self::Insert => "insert",
+            Self::Remove => "remove",
+            Self::Contains => "contains",
+            Self::Join => "join",
+        }
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Self::Pop => 0,
+            Self::Push | Self::Remove | Self::Contains | Self::Join => 1,
+            Self::Insert => 2,
+        }
+    }
+}
+
+/// Position `index` refers to in a list of `len` elements, counting from the end when negative.
+pub fn position(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+/// Like [`position`], but also accepts the position right after the last element.
+pub fn insertion_position(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { index + len as i64 } else { index };
+
+    (0..=len as i64).contains(&index).then_some(index as usize)
+}
+
+/// Elements selected by `list[start:end]`. Bounds count from the end when negative
+/// and are clamped to the list, so slicing never fails.
+pub fn slice(start: Option<i64>, end: Option<i64>, len: usize) -> Range<usize> {
+    let clamp = |bound: i64| {
+        let bound = if bound < 0 { bound + len as i64 } else { bound };
+        bound.clamp(0, len as i64) as usize
+    };
+
+    let start = start.map(clamp).unwrap_or(0);
+    let end = end.map(clamp).unwrap_or(len);
+
+    start..end.max(start)
+}
+
+pub fn out_of_bounds(index: i64, len: usize) -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::IndexOutOfBounds(
+        index, len,
+    )))
+}
+
+thread_local! {
+    /// Lists being written out, to cut the recursion when a list contains itself.
+    static DISPLAYED: RefCell<Vec<*const ()>> = const { RefCell::new(vec![]) };
+}
+
+/// Writes `items` as `[a, b, c]`, or `[...]` if the list at `address` is already being written.
+pub fn display<I: IntoIterator>(
+    f: &mut fmt::Formatter<'_>,
+    address: *const (),
+    items: I,
+    item: impl FnMut(&mut fmt::Formatter<'_>, I::Item) -> fmt::Result,
+) -> fmt::Result {
+    display_delimited(f, address, ("[", "]"), items, item)
+}
+
+/// Writes `items` separated by commas between `open` and `close`, guarding against cycles
+/// like [`display`].
+pub(crate) fn display_delimited<I: IntoIterator>(
+    f: &mut fmt::Formatter<'_>,
+    address: *const (),
+    (open, close): (&str, &str),
+    items: I,
+    mut item: impl FnMut(&mut fmt::Formatter<'_>, I::Item) -> fmt::Result,
+) -> fmt::Result {
+    if DISPLAYED.with_borrow(|displayed| displayed.contains(&address)) {
+        return write!(f, "{open}...{close}");
+    }
+
+    DISPLAYED.with_borrow_mut(|displayed| displayed.push(address));
+
+    let result = (|| {
+        write!(f, "{open}")?;
+        for (index, value) in items.into_iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            item(f, value)?;
+        }
+        write!(f, "{close}")
+    })();
+
+    DISPLAYED.with_borrow_mut(|displayed| displayed.pop());
+
+    result
+}
+
+/// A list method bound to its list, as produced by `xs.push`.
+#[derive(Debug)]
+pub struct BoundListMethod {
+    list: Rc<RuntimeValue>,
+    method: ListMethod,
+}
+
+impl BoundListMethod {
+    pub fn new(list: Rc<RuntimeValue>, method: ListMethod) -> Self {
+        Self { list, method }
+    }
+}
+
+impl NativeFunction for BoundListMethod {
+    fn name(&self) -> &str {
+        self.method.name()
+    }
+
+    fn arity(&self) -> usize {
+        self.method.arity()
+    }
+
+    fn call(
+        &self,
+        runtime: &Runtime,
+        arguments: &[Rc<RuntimeValue>],
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let RuntimeValue::List(values) = self.list.as_ref() else {
+            unreachable!()
+        };
+
+        let index = |argument: &Rc<RuntimeValue>| match argument.as_ref() {
+            RuntimeValue::Integer(index) => Ok(*index),
+            _ => Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::IndexNotInteger,
+            ))),
+        };
+
+        match self.method {
+            ListMethod::Push => {
+                values.borrow_mut().push(Rc::clone(&arguments[0]));
+                runtime.charge(size_of::<Rc<RuntimeValue>>())?;
+
+                Ok(Rc::new(RuntimeValue::nil()))
+            }
+            ListMethod::Pop => values
+                .borrow_mut()
+                .pop()
+                .ok_or_else(|| out_of_bounds(-1, 0)),
+            ListMethod::Insert => {
+                let index = index(&arguments[0])?;
+                let len = values.borrow().len();
+                let position =
+                    insertion_position(index, len).ok_or_else(|| out_of_bounds(index, len))?;
+
+                values
+                    .borrow_mut()
+                    .insert(position, Rc::clone(&arguments[1]));
+                runtime.charge(size_of::<Rc<RuntimeValue>>())?;
+
+                Ok(Rc::new(RuntimeValue::nil()))
+            }
+            ListMethod::Remove => {
+                let index = index(&arguments[0])?;
+                let len = values.borrow().len();
+                let position = position(index, len).ok_or_else(|| out_of_bounds(index, len))?;
+
+                Ok(values.borrow_mut().remove(position))
+            }
+            ListMethod::Contains => {
+                let contains = values
+                    .borrow()
+                    .iter()
+                    .any(|value| value.as_ref() == arguments[0].as_ref());
+
+                Ok(Rc::new(RuntimeValue::boolean(contains)))
+            }
+            ListMethod::Join => {
+                let RuntimeValue::String(separator) = arguments[0].as_ref() else {
+                    return Err(invalid_argument("join", "a string"));
+                };
+
+                let joined = values
+                    .borrow()
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(separator);
+
+                runtime.allocate(RuntimeValue::string(joined))
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt, mem::size_of, rc::Rc};
+
+use error::InterpreterError;
+use indexmap::IndexMap;
+
+use crate::{
+    error::{RuntimeError, RuntimeErrorKind, RuntimeResult},
+    Runtime,
+};
+
+use super::{list, native::NativeFunction, value::RuntimeValue};
+
+/// Entries of a map in insertion order. Each key is stored next to its value so the
+/// original key (e.g. `1.0` rather than `1`) can be handed back by `keys()`.
+pub type Entries<V> = IndexMap<MapKey, (V, V)>;
+
+/// Hashable form of a map key.
+///
+/// Keys are equal exactly when the values they come from compare equal, so a float
+/// with an integral value shares the key of the matching integer: `m[1]` and `m[1.0]`
+/// name the same entry. Only nil, booleans, numbers other than NaN and strings can
+/// be keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Nil,
+    Boolean(bool),
+    Integer(i64),
+    /// Bit pattern of a float without an integral value.
+    Float(u64),
+    String(String),
+}
+
+impl MapKey {
+    /// Key of a float, or `None` for NaN, which is not even equal to itself.
+    pub fn float(value: f64) -> Option<Self> {
+        if value.is_nan() {
+            return None;
+        }
+
+        // `i64::MAX as f64` rounds up to 2^63, which is already out of range
+        if value.fract() == 0.0 && value >= i64::MIN as f64 && value < i64::MAX as f64 {
+            Some(Self::Integer(value as i64))
+        } else {
+            Some(Self::Float(value.to_bits()))
+        }
+    }
+
+    /// Approximate number of bytes used by an entry with this key.
+    pub fn entry_size<V>(&self) -> usize {
+        size_of::<Self>()
+            + 2 * size_of::<V>()
+            + match self {
+                Self::String(value) => value.capacity(),
+                _ => 0,
+            }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nil => write!(f, "nil"),
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Float(bits) => write!(f, "{}", f64::from_bits(*bits)),
+            Self::String(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// Methods available on every map, e.g. `m.keys()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapMethod {
+    Keys,
+    Values,
+    Has,
+    Delete,
+}
+
+impl MapMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "keys" => Some(Self::Keys),
+            "values" => Some(Self::Values),
+            "has" => Some(Self::Has),
+            "delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keys => "keys",
+            Self::Values => "values",
+            Self::Has => "has",
+            Self::Delete => "delete",
+        }
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Self::Keys | Self::Values => 0,
+            Self::Has | Self::Delete => 1,
+        }
+    }
+}
+
+/// Sets the value stored under `key`. An existing entry keeps its original key and
+/// position. Returns whether a new entry was added.
+pub fn insert<V>(entries: &mut Entries<V>, key: MapKey, original: V, value: V) -> bool {
+    match entries.get_mut(&key) {
+        Some((_, existing)) => {
+            *existing = value;
+            false
+        }
+        None => {
+            entries.insert(key, (original, value));
+            true
+        }
+    }
+}
+
+pub fn invalid_key() -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::InvalidMapKey))
+}
+
+pub fn missing_key(key: &MapKey) -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::KeyNotFound(
+        key.to_string(),
+    )))
+}
+
+/// Writes `entries` as `{k: v}`, or `{...}` if the map at `address` is already being written.
+pub fn display<V>(
+    f: &mut fmt::Formatter<'_>,
+    address: *const (),
+    entries: &Entries<V>,
+    mut item: impl FnMut(&mut fmt::Formatter<'_>, &V) -> fmt::Result,
+) -> fmt::Result {
+    list::display_delimited(
+        f,
+        address,
+        ("{", "}"),
+        entries.values(),
+        |f, (key, value)| {
+            item(f, key)?;
+            write!(f, ": ")?;
+            item(f, value)
+        },
+    )
+}
+
+/// A map method bound to its map, as produced by `m.keys`.
+#[derive(Debug)]
+pub struct BoundMapMethod {
+    map: Rc<RuntimeValue>,
+    method: MapMethod,
+}
+
+impl BoundMapMethod {
+    pub fn new(map: Rc<RuntimeValue>, method: MapMethod) -> Self {
+        Self { map, method }
+    }
+}
+
+impl NativeFunction for BoundMapMethod {
+    fn name(&self) -> &str {
+        self.method.name()
+    }
+
+    fn arity(&self) -> usize {
+        self.method.arity()
+    }
+
+    fn call(
+        &self,
+        runtime: &Runtime,
+        arguments: &[Rc<RuntimeValue>],
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let RuntimeValue::Map(entries) = self.map.as_ref() else {
+            unreachable!()
+        };
+
+        match self.method {
+            MapMethod::Keys => {
+                let keys = entries
+                    .borrow()
+                    .values()
+                    .map(|(key, _)| Rc::clone(key))
+                    .collect();
+
+                runtime.allocate(RuntimeValue::list(keys))
+            }
+            MapMethod::Values => {
+                let values = entries
+                    .borrow()
+                    .values()
+                    .map(|(_, value)| Rc::clone(value))
+                    .collect();
+
+                runtime.allocate(RuntimeValue::list(values))
+            }
+            MapMethod::Has => {
+                let key = arguments[0].key().ok_or_else(invalid_key)?;
+                let has = entries.borrow().contains_key(&key);
+
+                Ok(Rc::new(RuntimeValue::boolean(has)))
+            }
+            MapMethod::Delete => {
+                let key = arguments[0].key().ok_or_else(invalid_key)?;
+
+                let removed = entries.borrow_mut().shift_remove(&key);
+
+                removed
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| missing_key(&key))
+            }
+        }
+    }
+}
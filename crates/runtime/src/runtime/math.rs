@@ -0,0 +1,180 @@
+use std::{f64::consts, rc::Rc};
+
+use crate::{error::RuntimeResult, Runtime};
+
+use super::{
+    native::{invalid_argument, Builtin, Library, NativeFunction},
+    value::RuntimeValue,
+};
+
+/// The `math` library. Functions keep integers as integers where the result
+/// allows it, e.g. `math.floor` and `math.abs` return integers.
+pub fn library() -> Library {
+    let functions = [
+        Builtin::new("sqrt", 1, sqrt),
+        Builtin::new("pow", 2, pow),
+        Builtin::new("abs", 1, abs),
+        Builtin::new("floor", 1, floor),
+        Builtin::new("ceil", 1, ceil),
+        Builtin::new("round", 1, round),
+        Builtin::new("min", 2, min),
+        Builtin::new("max", 2, max),
+        Builtin::new("sin", 1, sin),
+        Builtin::new("cos", 1, cos),
+        Builtin::new("tan", 1, tan),
+        Builtin::new("log", 1, log),
+        Builtin::new("exp", 1, exp),
+        Builtin::new("isNan", 1, is_nan),
+    ];
+
+    let constants = [
+        ("pi", consts::PI),
+        ("e", consts::E),
+        ("inf", f64::INFINITY),
+        ("nan", f64::NAN),
+    ];
+
+    Library::new(
+        "math",
+        functions
+            .into_iter()
+            .map(|function| (function.name().to_string(), RuntimeValue::native(function)))
+            .chain(
+                constants
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), RuntimeValue::float(value))),
+            )
+            .collect(),
+    )
+}
+
+fn number(function: &str, value: &RuntimeValue) -> RuntimeResult<f64> {
+    match value {
+        RuntimeValue::Integer(value) => Ok(*value as f64),
+        RuntimeValue::Float(value) => Ok(*value),
+        _ => Err(invalid_argument(function, "a number")),
+    }
+}
+
+/// Applies `operation` to the argument as a float.
+fn float(
+    function: &str,
+    value: &RuntimeValue,
+    operation: fn(f64) -> f64,
+) -> RuntimeResult<Rc<RuntimeValue>> {
+    let value = number(function, value)?;
+
+    Ok(Rc::new(RuntimeValue::float(operation(value))))
+}
+
+/// Rounds the argument with `operation`, giving an integer unless the result is
+/// infinite, NaN or out of the integer range.
+fn integral(
+    function: &str,
+    value: &RuntimeValue,
+    operation: fn(f64) -> f64,
+) -> RuntimeResult<Rc<RuntimeValue>> {
+    let rounded = match value {
+        RuntimeValue::Integer(value) => return Ok(Rc::new(RuntimeValue::integer(*value))),
+        value => operation(number(function, value)?),
+    };
+
+    // `i64::MAX as f64` rounds up to 2^63, which is already out of range
+    Ok(Rc::new(
+        if rounded >= i64::MIN as f64 && rounded < i64::MAX as f64 {
+            RuntimeValue::integer(rounded as i64)
+        } else {
+            RuntimeValue::float(rounded)
+        },
+    ))
+}
+
+fn sqrt(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    float("sqrt", &arguments[0], f64::sqrt)
+}
+
+/// Integer power of integers when the exponent is not negative and the result
+/// fits, float power otherwise.
+fn pow(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    if let (RuntimeValue::Integer(base), RuntimeValue::Integer(exponent)) =
+        (arguments[0].as_ref(), arguments[1].as_ref())
+    {
+        if let Some(power) = u32::try_from(*exponent)
+            .ok()
+            .and_then(|exponent| base.checked_pow(exponent))
+        {
+            return Ok(Rc::new(RuntimeValue::integer(power)));
+        }
+    }
+
+    let base = number("pow", &arguments[0])?;
+    let exponent = number("pow", &arguments[1])?;
+
+    Ok(Rc::new(RuntimeValue::float(base.powf(exponent))))
+}
+
+fn abs(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    match arguments[0].as_ref() {
+        RuntimeValue::Integer(value) => Ok(Rc::new(match value.checked_abs() {
+            Some(value) => RuntimeValue::integer(value),
+            None => RuntimeValue::float((*value as f64).abs()),
+        })),
+        value => float("abs", value, f64::abs),
+    }
+}
+
+fn floor(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    integral("floor", &arguments[0], f64::floor)
+}
+
+fn ceil(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    integral("ceil", &arguments[0], f64::ceil)
+}
+
+/// Rounds half-way cases away from zero.
+fn round(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    integral("round", &arguments[0], f64::round)
+}
+
+/// Smaller of the two arguments, returned as it was passed.
+fn min(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let (lhs, rhs) = (number("min", &arguments[0])?, number("min", &arguments[1])?);
+
+    Ok(Rc::clone(&arguments[if rhs < lhs { 1 } else { 0 }]))
+}
+
+/// Larger of the two arguments, returned as it was passed.
+fn max(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let (lhs, rhs) = (number("max", &arguments[0])?, number("max", &arguments[1])?);
+
+    Ok(Rc::clone(&arguments[if rhs > lhs { 1 } else { 0 }]))
+}
+
+fn sin(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    float("sin", &arguments[0], f64::sin)
+}
+
+fn cos(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    float("cos", &arguments[0], f64::cos)
+}
+
+fn tan(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    float("tan", &arguments[0], f64::tan)
+}
+
+/// Natural logarithm.
+fn log(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    float("log", &arguments[0], f64::ln)
+}
+
+fn exp(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    float("exp", &arguments[0], f64::exp)
+}
+
+fn is_nan(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    match arguments[0].as_ref() {
+        RuntimeValue::Integer(_) => Ok(Rc::new(RuntimeValue::boolean(false))),
+        RuntimeValue::Float(value) => Ok(Rc::new(RuntimeValue::boolean(value.is_nan()))),
+        _ => Err(invalid_argument("isNan", "a number")),
+    }
+}
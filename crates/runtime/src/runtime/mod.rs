@@ -1,3 +1,15 @@
+pub mod capabilities;
+pub mod convert;
 pub mod environment;
+pub mod heap;
+pub mod io;
+pub mod limits;
+pub mod list;
+pub mod map;
+pub mod math;
+pub mod module;
+pub mod native;
 pub mod signal;
+pub mod string;
+pub mod system;
 pub mod value;
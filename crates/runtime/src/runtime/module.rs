@@ -0,0 +1,290 @@
+use std::{
+    cell::RefCell,
+    env, fs,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use ast::statement::{Statement, StatementKind};
+use error::InterpreterError;
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+
+use crate::error::{RuntimeError, RuntimeErrorKind, RuntimeResult};
+
+use super::{capabilities::Capability, value::RuntimeValue};
+
+/// File of a module together with the source it was compiled from, so errors in
+/// the module are shown as it was run even if the file changes afterwards.
+#[derive(Debug)]
+pub struct ModuleFile {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// Finds the files named by `import` statements.
+///
+/// A module path is looked up relative to the directory of the importing file
+/// first, then relative to each directory of the search path in order. Modules
+/// outside the main program's directory and the search path can only be
+/// imported with the `fs` capability.
+#[derive(Debug, Default)]
+pub struct ModuleLoader {
+    /// File the main program was read from, if it came from one.
+    main: RefCell<Option<PathBuf>>,
+    search_path: RefCell<Vec<PathBuf>>,
+}
+
+impl ModuleLoader {
+    /// Records the file the main program was read from. Imports in the program
+    /// are resolved relative to it, and modules importing it form a cycle.
+    pub fn set_main(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        self.main.replace(Some(path));
+    }
+
+    /// Forgets the file recorded by [`ModuleLoader::set_main`], so imports in the
+    /// main program are resolved relative to the working directory again.
+    pub fn clear_main(&self) {
+        self.main.replace(None);
+    }
+
+    /// Adds a directory searched for modules not found next to the importing file.
+    pub fn add_search_path(&self, directory: impl Into<PathBuf>) {
+        self.search_path.borrow_mut().push(directory.into());
+    }
+
+    /// Canonical path of the module `specifier` imported from the file `importer`,
+    /// or from the main program if `None`. Without `fs`, only candidates in the
+    /// exposed directories are looked at, so a denied import reveals nothing about
+    /// the files outside them.
+    pub fn resolve(
+        &self,
+        specifier: &str,
+        importer: Option<&Path>,
+        fs: bool,
+    ) -> RuntimeResult<PathBuf> {
+        let main = self.main.borrow();
+        let directory = importer
+            .or(main.as_deref())
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+
+        let search_path = self.search_path.borrow();
+        let exposed = self.exposed();
+
+        let candidates =
+            std::iter::once(directory)
+                .chain(search_path.iter().map(|directory| {
+                    fs::canonicalize(directory).unwrap_or_else(|_| directory.clone())
+                }))
+                .map(|directory| normalize(&directory.join(specifier)))
+                .filter(|candidate| fs || is_below(candidate, &exposed))
+                .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return Err(denied());
+        }
+
+        let path = candidates
+            .into_iter()
+            .find(|candidate| candidate.is_file())
+            .and_then(|candidate| fs::canonicalize(candidate).ok())
+            .ok_or_else(|| {
+                InterpreterError::new(RuntimeError::new(RuntimeErrorKind::ModuleNotFound(
+                    specifier.to_string(),
+                )))
+            })?;
+
+        // a link in an exposed directory may still lead out of it
+        if !fs && !is_below(&path, &exposed) {
+            return Err(denied());
+        }
+
+        Ok(path)
+    }
+
+    /// Fails if `path` is the main program or one of the modules still being
+    /// loaded, listed from the outermost import.
+    pub fn check_cycle(&self, loading: &[&Path], path: &Path) -> RuntimeResult<()> {
+        let main = self.main.borrow();
+        let chain = main
+            .as_deref()
+            .into_iter()
+            .chain(loading.iter().copied())
+            .collect::<Vec<_>>();
+
+        match chain.iter().position(|loaded| *loaded == path) {
+            Some(start) => {
+                let cycle = chain[start..]
+                    .iter()
+                    .copied()
+                    .chain([path])
+                    .map(display)
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+
+                Err(InterpreterError::new(RuntimeError::new(
+                    RuntimeErrorKind::ImportCycle(cycle),
+                )))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Directories of the main program and of the search path, which the host
+    /// chose to expose to programs without the `fs` capability.
+    fn exposed(&self) -> Vec<PathBuf> {
+        let main = self.main.borrow();
+        let search_path = self.search_path.borrow();
+
+        main.as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .into_iter()
+            .chain(
+                search_path
+                    .iter()
+                    .filter_map(|directory| fs::canonicalize(directory).ok()),
+            )
+            .collect()
+    }
+}
+
+fn is_below(path: &Path, directories: &[PathBuf]) -> bool {
+    directories
+        .iter()
+        .any(|directory| path.starts_with(directory))
+}
+
+/// Absolute form of `path` with `.` and `..` applied to the components before
+/// them, without looking at the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = env::current_dir().unwrap_or_default();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+fn denied() -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::PermissionDenied(
+        "import".to_string(),
+        Capability::Fs,
+    )))
+}
+
+/// Reads and resolves the module at `path`.
+pub fn load(path: &Path) -> RuntimeResult<(Rc<ModuleFile>, Vec<Rc<Statement>>)> {
+    let source = fs::read_to_string(path)
+        .map_err(|error| InterpreterError::new(RuntimeError::new(RuntimeErrorKind::Io(error))))?;
+    let file = Rc::new(ModuleFile {
+        path: path.to_path_buf(),
+        source,
+    });
+
+    let scanner = Scanner::new(file.source.clone());
+    if let Err(errors) = scanner.scan_tokens() {
+        return Err(invalid(&file, &errors[0]));
+    }
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    if let Some(error) = errors.first() {
+        return Err(invalid(&file, error));
+    }
+
+    Resolver::new()
+        .run(&program)
+        .map_err(|error| invalid(&file, &error))?;
+
+    Ok((file, program))
+}
+
+/// Names of the bindings a module exports.
+pub fn exports(program: &[Rc<Statement>]) -> Vec<String> {
+    program
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::Export(declaration) => match &declaration.kind {
+                StatementKind::VariableDeclaration { identifier, .. }
+                | StatementKind::FunctionDeclaration { identifier, .. }
+                | StatementKind::ClassDeclaration { identifier, .. } => Some(identifier.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Name of the module at `path`, its file name without the extension.
+pub fn name(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Path a module is shown with in errors, relative to the working directory when below it.
+pub fn display(path: &Path) -> String {
+    env::current_dir()
+        .ok()
+        .and_then(|directory| path.strip_prefix(directory).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Current value of the binding `name` exported by `module`.
+pub fn export(module: &RuntimeValue, name: &str) -> RuntimeResult<Rc<RuntimeValue>> {
+    let RuntimeValue::Module {
+        name: module,
+        globals,
+        exports,
+    } = module
+    else {
+        unreachable!()
+    };
+
+    exports
+        .iter()
+        .any(|export| export == name)
+        .then(|| globals.get(name))
+        .flatten()
+        .ok_or_else(|| undefined_export(module, name))
+}
+
+pub fn undefined_export(module: &str, name: &str) -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::UndefinedExport(
+        module.to_string(),
+        name.to_string(),
+    )))
+}
+
+/// Error for a module that failed to compile, pointing at the first problem in its file.
+pub fn invalid<E: error::Error>(
+    file: &Rc<ModuleFile>,
+    error: &InterpreterError<E>,
+) -> InterpreterError<RuntimeError> {
+    let mut invalid = RuntimeError::new(RuntimeErrorKind::InvalidModule(
+        display(&file.path),
+        error.source().to_string(),
+    ));
+
+    if let Some(span) = error.source().span() {
+        invalid.locate_in(span, Some(file));
+    }
+
+    InterpreterError::new(invalid)
+}
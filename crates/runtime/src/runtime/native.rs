@@ -0,0 +1,227 @@
+use std::{
+    fmt,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use error::InterpreterError;
+
+use crate::{
+    error::{RuntimeError, RuntimeErrorKind, RuntimeResult},
+    Runtime,
+};
+
+use super::{math, system, value::RuntimeValue};
+
+/// A function implemented in Rust and callable from Lox.
+pub trait NativeFunction {
+    fn name(&self) -> &str;
+    fn arity(&self) -> usize;
+    /// Called with exactly [`NativeFunction::arity`] arguments.
+    fn call(
+        &self,
+        runtime: &Runtime,
+        arguments: &[Rc<RuntimeValue>],
+    ) -> RuntimeResult<Rc<RuntimeValue>>;
+}
+
+impl fmt::Debug for dyn NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NativeFunction({})", self.name())
+    }
+}
+
+type NativeBody = fn(&Runtime, &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>>;
+
+/// Native function backed by a plain Rust function pointer.
+pub struct Builtin {
+    name: &'static str,
+    arity: usize,
+    body: NativeBody,
+}
+
+impl Builtin {
+    pub const fn new(name: &'static str, arity: usize, body: NativeBody) -> Self {
+        Self { name, arity, body }
+    }
+}
+
+impl NativeFunction for Builtin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn call(
+        &self,
+        runtime: &Runtime,
+        arguments: &[Rc<RuntimeValue>],
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        (self.body)(runtime, arguments)
+    }
+}
+
+/// Natives and constants grouped under one global, through which Lox code
+/// reaches them like the exports of a module, e.g. `math.sqrt`.
+pub struct Library {
+    name: &'static str,
+    members: Vec<(String, RuntimeValue)>,
+}
+
+impl Library {
+    pub fn new(name: &'static str, members: Vec<(String, RuntimeValue)>) -> Self {
+        Self { name, members }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn into_members(self) -> Vec<(String, RuntimeValue)> {
+        self.members
+    }
+}
+
+/// Libraries defined in the global environment of every runtime.
+pub fn libraries() -> Vec<Library> {
+    vec![math::library()]
+}
+
+/// Builtins defined in the global environment of every runtime.
+pub fn core() -> Vec<Builtin> {
+    let mut builtins = vec![
+        Builtin::new("clock", 0, clock),
+        Builtin::new("type", 1, type_of),
+        Builtin::new("len", 1, len),
+        Builtin::new("assert", 2, assert),
+        Builtin::new("str", 1, str),
+        Builtin::new("num", 1, num),
+        Builtin::new("ord", 1, ord),
+        Builtin::new("chr", 1, chr),
+    ];
+
+    builtins.extend(system::natives());
+    builtins
+}
+
+pub fn invalid_argument(function: &str, expected: &str) -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::InvalidArgumentType(
+        function.to_string(),
+        expected.to_string(),
+    )))
+}
+
+/// Seconds elapsed since the Unix epoch.
+fn clock(_: &Runtime, _: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    Ok(Rc::new(RuntimeValue::float(elapsed.as_secs_f64())))
+}
+
+fn type_of(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.allocate(RuntimeValue::string(arguments[0].type_name().to_string()))
+}
+
+fn len(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    match arguments[0].as_ref() {
+        RuntimeValue::String(value) => {
+            Ok(Rc::new(RuntimeValue::integer(value.chars().count() as i64)))
+        }
+        RuntimeValue::List(values) => {
+            Ok(Rc::new(RuntimeValue::integer(values.borrow().len() as i64)))
+        }
+        RuntimeValue::Map(entries) => Ok(Rc::new(RuntimeValue::integer(
+            entries.borrow().len() as i64
+        ))),
+        RuntimeValue::Foreign(value) => value
+            .length()
+            .map(|length| Rc::new(RuntimeValue::integer(length as i64)))
+            .ok_or_else(|| invalid_argument("len", "a string, a list or a map")),
+        _ => Err(invalid_argument("len", "a string, a list or a map")),
+    }
+}
+
+fn assert(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    if <_ as Into<bool>>::into(arguments[0].as_ref()) {
+        Ok(Rc::new(RuntimeValue::nil()))
+    } else {
+        Err(InterpreterError::new(RuntimeError::new(
+            RuntimeErrorKind::AssertionFailed(arguments[1].to_string()),
+        )))
+    }
+}
+
+/// The value as `print` would show it.
+fn str(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.allocate(RuntimeValue::string(arguments[0].to_string()))
+}
+
+/// Number written in the string like a number literal, optionally negative and
+/// surrounded by whitespace. Integral values become integers, as literals do,
+/// unless they are too large for one.
+fn num(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let RuntimeValue::String(text) = arguments[0].as_ref() else {
+        return Err(invalid_argument("num", "a string"));
+    };
+
+    let trimmed = text.trim();
+    let unsigned = trimmed.strip_prefix('-').unwrap_or(trimmed);
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+
+    let literal = match unsigned.split_once('.') {
+        Some((whole, fraction)) => digits(whole) && digits(fraction),
+        None => digits(unsigned),
+    };
+
+    let value = literal
+        .then(|| trimmed.parse::<f64>().ok())
+        .flatten()
+        .ok_or_else(|| {
+            InterpreterError::new(RuntimeError::new(RuntimeErrorKind::InvalidNumber(
+                text.to_string(),
+            )))
+        })?;
+
+    // parsed separately so integers keep the digits a float would round away
+    if let Ok(value) = trimmed.parse::<i64>() {
+        return Ok(Rc::new(RuntimeValue::integer(value)));
+    }
+
+    let integral = value.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(&value);
+
+    Ok(Rc::new(if integral {
+        RuntimeValue::integer(value as i64)
+    } else {
+        RuntimeValue::float(value)
+    }))
+}
+
+/// Code point of a single-character string.
+fn ord(_: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let character = match arguments[0].as_ref() {
+        RuntimeValue::String(value) => {
+            let mut chars = value.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }
+        _ => None,
+    }
+    .ok_or_else(|| invalid_argument("ord", "a single character"))?;
+
+    Ok(Rc::new(RuntimeValue::integer(character as i64)))
+}
+
+/// Single-character string of a code point.
+fn chr(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    let character = match arguments[0].as_ref() {
+        RuntimeValue::Integer(value) => u32::try_from(*value).ok().and_then(char::from_u32),
+        _ => None,
+    }
+    .ok_or_else(|| invalid_argument("chr", "a Unicode code point"))?;
+
+    runtime.allocate(RuntimeValue::string(character.to_string()))
+}
@@ -1,5 +1,7 @@
 use std::rc::Rc;
 
+use error::span::Span;
+
 use super::value::RuntimeValue;
 
 #[derive(Debug)]
@@ -7,4 +9,6 @@ pub enum RuntimeSignal {
     LoopBreak,
     LoopContinue,
     FunctionReturn(Rc<RuntimeValue>),
+    /// Value of a `throw` statement, unwinding to the closest `try`.
+    Throw(Rc<RuntimeValue>, Span),
 }
@@ -0,0 +1,221 @@
+use std::rc::Rc;
+
+use error::InterpreterError;
+
+use crate::{
+    error::{RuntimeError, RuntimeErrorKind, RuntimeResult},
+    Runtime,
+};
+
+use super::{
+    list,
+    native::{invalid_argument, NativeFunction},
+    value::RuntimeValue,
+};
+
+/// Methods available on every string, e.g. `s.upper()`. Positions count characters,
+/// like `len` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringMethod {
+    Substring,
+    IndexOf,
+    Split,
+    Trim,
+    Upper,
+    Lower,
+    Replace,
+    StartsWith,
+    EndsWith,
+    Repeat,
+    Chars,
+}
+
+impl StringMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "substring" => Some(Self::Substring),
+            "indexOf" => Some(Self::IndexOf),
+            "split" => Some(Self::Split),
+            "trim" => Some(Self::Trim),
+            "upper" => Some(Self::Upper),
+            "lower" => Some(Self::Lower),
+            "replace" => Some(Self::Replace),
+            "startsWith" => Some(Self::StartsWith),
+            "endsWith" => Some(Self::EndsWith),
+            "repeat" => Some(Self::Repeat),
+            "chars" => Some(Self::Chars),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Substring => "substring",
+            Self::IndexOf => "indexOf",
+            Self::Split => "split",
+            Self::Trim => "trim",
+            Self::Upper => "upper",
+            Self::Lower => "lower",
+            Self::Replace => "replace",
+            Self::StartsWith => "startsWith",
+            Self::EndsWith => "endsWith",
+            Self::Repeat => "repeat",
+            Self::Chars => "chars",
+        }
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Self::Trim | Self::Upper | Self::Lower | Self::Chars => 0,
+            Self::IndexOf | Self::Split | Self::StartsWith | Self::EndsWith | Self::Repeat => 1,
+            Self::Substring | Self::Replace => 2,
+        }
+    }
+}
+
+/// A string method bound to its string, as produced by `s.upper`.
+#[derive(Debug)]
+pub struct BoundStringMethod {
+    string: Rc<RuntimeValue>,
+    method: StringMethod,
+}
+
+impl BoundStringMethod {
+    pub fn new(string: Rc<RuntimeValue>, method: StringMethod) -> Self {
+        Self { string, method }
+    }
+
+    fn string_argument<'a>(&self, argument: &'a RuntimeValue) -> RuntimeResult<&'a str> {
+        match argument {
+            RuntimeValue::String(value) => Ok(value),
+            _ => Err(invalid_argument(self.method.name(), "a string")),
+        }
+    }
+}
+
+impl NativeFunction for BoundStringMethod {
+    fn name(&self) -> &str {
+        self.method.name()
+    }
+
+    fn arity(&self) -> usize {
+        self.method.arity()
+    }
+
+    fn call(
+        &self,
+        runtime: &Runtime,
+        arguments: &[Rc<RuntimeValue>],
+    ) -> RuntimeResult<Rc<RuntimeValue>> {
+        let RuntimeValue::String(string) = self.string.as_ref() else {
+            unreachable!()
+        };
+
+        let index = |argument: &Rc<RuntimeValue>| match argument.as_ref() {
+            RuntimeValue::Integer(index) => Ok(*index),
+            _ => Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::IndexNotInteger,
+            ))),
+        };
+
+        let strings = |parts: Vec<String>| {
+            let parts = parts
+                .into_iter()
+                .map(|part| runtime.allocate(RuntimeValue::string(part)))
+                .collect::<RuntimeResult<_>>()?;
+
+            runtime.allocate(RuntimeValue::list(parts))
+        };
+
+        match self.method {
+            StringMethod::Substring => {
+                let (start, end) = (index(&arguments[0])?, index(&arguments[1])?);
+                let range = list::slice(Some(start), Some(end), string.chars().count());
+
+                let substring = string.chars().skip(range.start).take(range.len()).collect();
+
+                runtime.allocate(RuntimeValue::string(substring))
+            }
+            StringMethod::IndexOf => {
+                let needle = self.string_argument(&arguments[0])?;
+                let position = string
+                    .find(needle)
+                    .map(|offset| string[..offset].chars().count() as i64)
+                    .unwrap_or(-1);
+
+                Ok(Rc::new(RuntimeValue::integer(position)))
+            }
+            StringMethod::Split => {
+                let separator = self.string_argument(&arguments[0])?;
+
+                // an empty separator splits between every character
+                let parts = if separator.is_empty() {
+                    string.chars().map(String::from).collect()
+                } else {
+                    string.split(separator).map(String::from).collect()
+                };
+
+                strings(parts)
+            }
+            StringMethod::Trim => runtime.allocate(RuntimeValue::string(string.trim().to_string())),
+            StringMethod::Upper => runtime.allocate(RuntimeValue::string(string.to_uppercase())),
+            StringMethod::Lower => runtime.allocate(RuntimeValue::string(string.to_lowercase())),
+            StringMethod::Replace => {
+                let from = self.string_argument(&arguments[0])?;
+                let to = self.string_argument(&arguments[1])?;
+
+                runtime.allocate(RuntimeValue::string(string.replace(from, to)))
+            }
+            StringMethod::StartsWith => {
+                let prefix = self.string_argument(&arguments[0])?;
+
+                Ok(Rc::new(RuntimeValue::boolean(string.starts_with(prefix))))
+            }
+            StringMethod::EndsWith => {
+                let suffix = self.string_argument(&arguments[0])?;
+
+                Ok(Rc::new(RuntimeValue::boolean(string.ends_with(suffix))))
+            }
+            StringMethod::Repeat => {
+                let count = match arguments[0].as_ref() {
+                    RuntimeValue::Integer(count) => usize::try_from(*count).ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid_argument("repeat", "a non-negative integer"))?;
+
+                let length = string.len().saturating_mul(count);
+
+                // checked up front, so an oversized result fails before it is built
+                runtime.ensure_available(length)?;
+
+                let repeated = repeat(string, count).ok_or_else(|| {
+                    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::AllocationFailed(
+                        length,
+                    )))
+                })?;
+
+                runtime.allocate(RuntimeValue::string(repeated))
+            }
+            StringMethod::Chars => strings(string.chars().map(String::from).collect()),
+        }
+    }
+}
+
+/// `string` repeated `count` times, or `None` if the result cannot be allocated.
+fn repeat(string: &str, count: usize) -> Option<String> {
+    let length = string.len().checked_mul(count)?;
+
+    let mut bytes = Vec::new();
+    bytes.try_reserve_exact(length).ok()?;
+
+    if count > 0 {
+        bytes.extend_from_slice(string.as_bytes());
+    }
+
+    // doubling the copied prefix, like `str::repeat`
+    while bytes.len() < length {
+        bytes.extend_from_within(..bytes.len().min(length - bytes.len()));
+    }
+
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,163 @@
+use std::{env, fs, io::Write, rc::Rc};
+
+use error::InterpreterError;
+
+use crate::{
+    error::{RuntimeError, RuntimeErrorKind, RuntimeResult},
+    Runtime,
+};
+
+use super::{
+    capabilities::Capability,
+    native::{invalid_argument, Builtin},
+    value::RuntimeValue,
+};
+
+/// Builtins reaching outside the runtime, each failing unless the capability it
+/// needs was granted.
+pub fn natives() -> Vec<Builtin> {
+    vec![
+        Builtin::new("readFile", 1, read_file),
+        Builtin::new("writeFile", 2, write_file),
+        Builtin::new("appendFile", 2, append_file),
+        Builtin::new("listDir", 1, list_dir),
+        Builtin::new("exists", 1, exists),
+        Builtin::new("readLine", 0, read_line),
+        Builtin::new("env", 1, env_var),
+        Builtin::new("args", 0, args),
+        Builtin::new("exit", 1, exit),
+    ]
+}
+
+fn io_error(error: std::io::Error) -> InterpreterError<RuntimeError> {
+    InterpreterError::new(RuntimeError::new(RuntimeErrorKind::Io(error)))
+}
+
+fn string<'a>(function: &str, value: &'a RuntimeValue) -> RuntimeResult<&'a str> {
+    match value {
+        RuntimeValue::String(value) => Ok(value),
+        _ => Err(invalid_argument(function, "a string")),
+    }
+}
+
+fn read_file(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Fs, "readFile")?;
+    let path = string("readFile", &arguments[0])?;
+
+    let contents = fs::read_to_string(path).map_err(io_error)?;
+
+    runtime.allocate(RuntimeValue::string(contents))
+}
+
+/// Replaces the file's contents, creating it if needed.
+fn write_file(
+    runtime: &Runtime,
+    arguments: &[Rc<RuntimeValue>],
+) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Fs, "writeFile")?;
+    let path = string("writeFile", &arguments[0])?;
+    let contents = string("writeFile", &arguments[1])?;
+
+    fs::write(path, contents).map_err(io_error)?;
+
+    Ok(Rc::new(RuntimeValue::nil()))
+}
+
+/// Adds to the end of the file, creating it if needed.
+fn append_file(
+    runtime: &Runtime,
+    arguments: &[Rc<RuntimeValue>],
+) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Fs, "appendFile")?;
+    let path = string("appendFile", &arguments[0])?;
+    let contents = string("appendFile", &arguments[1])?;
+
+    fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(io_error)?;
+
+    Ok(Rc::new(RuntimeValue::nil()))
+}
+
+/// Names of the entries in the directory, sorted.
+fn list_dir(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Fs, "listDir")?;
+    let path = string("listDir", &arguments[0])?;
+
+    let mut names = fs::read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(io_error)?;
+    names.sort();
+
+    let names = names
+        .into_iter()
+        .map(|name| runtime.allocate(RuntimeValue::string(name)))
+        .collect::<RuntimeResult<_>>()?;
+
+    runtime.allocate(RuntimeValue::list(names))
+}
+
+fn exists(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Fs, "exists")?;
+    let path = string("exists", &arguments[0])?;
+
+    let exists = fs::exists(path).map_err(io_error)?;
+
+    Ok(Rc::new(RuntimeValue::boolean(exists)))
+}
+
+/// Next line of the runtime's input, or `nil` once it is exhausted.
+fn read_line(runtime: &Runtime, _: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Input, "readLine")?;
+
+    match runtime.read_line()? {
+        Some(line) => runtime.allocate(RuntimeValue::string(line)),
+        None => Ok(Rc::new(RuntimeValue::nil())),
+    }
+}
+
+/// Value of the environment variable, or `nil` if it is not set.
+fn env_var(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Env, "env")?;
+    let name = string("env", &arguments[0])?;
+
+    match env::var(name) {
+        Ok(value) => runtime.allocate(RuntimeValue::string(value)),
+        Err(_) => Ok(Rc::new(RuntimeValue::nil())),
+    }
+}
+
+/// Arguments the host passed to the program.
+fn args(runtime: &Runtime, _: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Env, "args")?;
+
+    let arguments = runtime
+        .arguments()
+        .into_iter()
+        .map(|argument| runtime.allocate(RuntimeValue::string(argument)))
+        .collect::<RuntimeResult<_>>()?;
+
+    runtime.allocate(RuntimeValue::list(arguments))
+}
+
+/// Stops the program, leaving it to the host to end the process with `code`.
+fn exit(runtime: &Runtime, arguments: &[Rc<RuntimeValue>]) -> RuntimeResult<Rc<RuntimeValue>> {
+    runtime.require(Capability::Exit, "exit")?;
+
+    let code = match arguments[0].as_ref() {
+        RuntimeValue::Integer(code) => i32::try_from(*code).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| invalid_argument("exit", "an exit code"))?;
+
+    Err(InterpreterError::new(RuntimeError::new(
+        RuntimeErrorKind::Exit(code),
+    )))
+}
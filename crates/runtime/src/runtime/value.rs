@@ -1,11 +1,23 @@
+use std::any::Any;
+use std::cell::RefCell;
 use std::cmp::PartialOrd;
+use std::collections::HashMap;
 use std::fmt;
+use std::mem::size_of;
 use std::ops::{Add, Div, Mul, Neg, Not, Sub};
 use std::rc::Rc;
 
 use ast::statement::Statement;
 
-use super::environment::Environment;
+use crate::error::RuntimeResult;
+
+use super::{
+    environment::Environment,
+    heap::Heap,
+    list,
+    map::{self, Entries, MapKey},
+    native::NativeFunction,
+};
 
 #[derive(Debug)]
 pub enum RuntimeValue {
@@ -19,6 +31,37 @@ pub enum RuntimeValue {
         closure: Rc<Environment>,
         parameters: Vec<String>,
     },
+    Class {
+        identifier: String,
+        superclass: Option<Rc<RuntimeValue>>,
+        methods: HashMap<String, Rc<RuntimeValue>>,
+    },
+    Instance {
+        class: Rc<RuntimeValue>,
+        fields: RefCell<HashMap<String, Rc<RuntimeValue>>>,
+    },
+    Native(Rc<dyn NativeFunction>),
+    List(RefCell<Vec<Rc<RuntimeValue>>>),
+    Map(RefCell<Entries<Rc<RuntimeValue>>>),
+    /// An imported module, exposing the exported globals of its environment.
+    Module {
+        name: String,
+        globals: Rc<Environment>,
+        exports: Vec<String>,
+    },
+    /// A value of another backend handed to a native as is, such as a VM list.
+    Foreign(Rc<dyn ForeignValue>),
+}
+
+/// A value owned by another backend, which natives can inspect and return but
+/// not look into.
+pub trait ForeignValue: fmt::Debug + fmt::Display {
+    /// Name of the value's type as reported by the `type` builtin.
+    fn type_name(&self) -> &'static str;
+    /// Number of elements, for the values `len` accepts.
+    fn length(&self) -> Option<usize>;
+    /// Lets the owning backend recover its value when a native returns it.
+    fn as_any(&self) -> &dyn Any;
 }
 
 impl RuntimeValue {
@@ -48,6 +91,172 @@ impl RuntimeValue {
             parameters,
         }
     }
+    pub fn class(
+        identifier: String,
+        superclass: Option<Rc<RuntimeValue>>,
+        methods: HashMap<String, Rc<RuntimeValue>>,
+    ) -> Self {
+        Self::Class {
+            identifier,
+            superclass,
+            methods,
+        }
+    }
+    pub fn instance(class: Rc<RuntimeValue>) -> Self {
+        Self::Instance {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+    pub fn native(function: impl NativeFunction + 'static) -> Self {
+        Self::Native(Rc::new(function))
+    }
+    pub fn list(values: Vec<Rc<RuntimeValue>>) -> Self {
+        Self::List(RefCell::new(values))
+    }
+    pub fn map(entries: Entries<Rc<RuntimeValue>>) -> Self {
+        Self::Map(RefCell::new(entries))
+    }
+
+    pub fn module(name: String, globals: Rc<Environment>, exports: Vec<String>) -> Self {
+        Self::Module {
+            name,
+            globals,
+            exports,
+        }
+    }
+}
+
+impl RuntimeValue {
+    /// Name of the value's type as reported by the `type` builtin.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RuntimeValue::Integer(_) => "integer",
+            RuntimeValue::Float(_) => "float",
+            RuntimeValue::String(_) => "string",
+            RuntimeValue::Nil => "nil",
+            RuntimeValue::Boolean(_) => "boolean",
+            RuntimeValue::Callable { .. } | RuntimeValue::Native(_) => "function",
+            RuntimeValue::Class { .. } => "class",
+            RuntimeValue::Instance { .. } => "instance",
+            RuntimeValue::List(_) => "list",
+            RuntimeValue::Map(_) => "map",
+            RuntimeValue::Module { .. } => "module",
+            RuntimeValue::Foreign(value) => value.type_name(),
+        }
+    }
+
+    /// Approximate number of bytes occupied by the value, excluding the environments
+    /// and other values it refers to.
+    pub fn size(&self) -> usize {
+        let entry = size_of::<String>() + size_of::<Rc<RuntimeValue>>();
+
+        size_of::<Self>()
+            + match self {
+                RuntimeValue::String(value) => value.capacity(),
+                RuntimeValue::Callable {
+                    execute,
+                    parameters,
+                    ..
+                } => {
+                    execute.len() * size_of::<Rc<Statement>>()
+                        + parameters
+                            .iter()
+                            .map(|parameter| size_of::<String>() + parameter.capacity())
+                            .sum::<usize>()
+                }
+                RuntimeValue::Class {
+                    identifier,
+                    methods,
+                    ..
+                } => identifier.capacity() + methods.len() * entry,
+                RuntimeValue::Instance { fields, .. } => fields
+                    .borrow()
+                    .keys()
+                    .map(|field| entry + field.capacity())
+                    .sum(),
+                RuntimeValue::List(values) => {
+                    values.borrow().capacity() * size_of::<Rc<RuntimeValue>>()
+                }
+                RuntimeValue::Map(entries) => entries
+                    .borrow()
+                    .keys()
+                    .map(MapKey::entry_size::<Rc<RuntimeValue>>)
+                    .sum(),
+                RuntimeValue::Module { name, exports, .. } => {
+                    name.capacity()
+                        + exports
+                            .iter()
+                            .map(|export| size_of::<String>() + export.capacity())
+                            .sum::<usize>()
+                }
+                _ => 0,
+            }
+    }
+
+    /// Key the value stands for when used to index a map, if it can be one.
+    pub fn key(&self) -> Option<MapKey> {
+        match self {
+            RuntimeValue::Nil => Some(MapKey::Nil),
+            RuntimeValue::Boolean(value) => Some(MapKey::Boolean(*value)),
+            RuntimeValue::Integer(value) => Some(MapKey::Integer(*value)),
+            RuntimeValue::Float(value) => MapKey::float(*value),
+            RuntimeValue::String(value) => Some(MapKey::String(value.clone())),
+            _ => None,
+        }
+    }
+
+    /// Writes the value as an element of a list or map, with strings quoted.
+    fn display_nested(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeValue::String(value) => write!(f, "{value:?}"),
+            value => write!(f, "{value}"),
+        }
+    }
+
+    /// Looks up a method declared on a class, walking up the superclass chain.
+    pub fn find_method(&self, identifier: &str) -> Option<Rc<RuntimeValue>> {
+        match self {
+            RuntimeValue::Class {
+                methods,
+                superclass,
+                ..
+            } => methods.get(identifier).map(Rc::clone).or_else(|| {
+                superclass
+                    .as_ref()
+                    .and_then(|superclass| superclass.find_method(identifier))
+            }),
+            _ => None,
+        }
+    }
+
+    /// Produces a copy of a callable whose closure has `this` bound to `instance`.
+    pub fn bind(
+        &self,
+        instance: Rc<RuntimeValue>,
+        heap: &Heap,
+    ) -> RuntimeResult<Option<RuntimeValue>> {
+        match self {
+            RuntimeValue::Callable {
+                execute,
+                closure,
+                parameters,
+            } => {
+                let environment = Environment::with_enclosing(Rc::clone(closure));
+                environment.define("this".to_string(), instance)?;
+
+                let environment = Rc::new(environment);
+                heap.track_environment(&environment);
+
+                Ok(Some(RuntimeValue::callable(
+                    parameters.clone(),
+                    execute.clone(),
+                    environment,
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 impl TryInto<i64> for &RuntimeValue {
@@ -94,15 +303,22 @@ impl<'a> TryInto<&'a str> for &'a RuntimeValue {
     }
 }
 
-impl Into<bool> for &RuntimeValue {
-    fn into(self) -> bool {
-        match self {
+impl From<&RuntimeValue> for bool {
+    fn from(val: &RuntimeValue) -> Self {
+        match val {
             RuntimeValue::Boolean(value) => *value,
             RuntimeValue::Float(value) => *value != 0.,
             RuntimeValue::Integer(value) => *value != 0,
             RuntimeValue::String(_) => true,
             RuntimeValue::Nil => false,
             RuntimeValue::Callable { .. } => true,
+            RuntimeValue::Class { .. } => true,
+            RuntimeValue::Instance { .. } => true,
+            RuntimeValue::Native(_) => true,
+            RuntimeValue::List(_) => true,
+            RuntimeValue::Map(_) => true,
+            RuntimeValue::Module { .. } => true,
+            RuntimeValue::Foreign(_) => true,
         }
     }
 }
@@ -126,12 +342,20 @@ impl Not for &RuntimeValue {
     }
 }
 
+/// Result of integer arithmetic, or `float` when it overflows, like `math.pow` does.
+fn integer_or_float(integer: Option<i64>, float: f64) -> RuntimeValue {
+    match integer {
+        Some(value) => RuntimeValue::Integer(value),
+        None => RuntimeValue::Float(float),
+    }
+}
+
 impl Neg for &RuntimeValue {
     type Output = Option<RuntimeValue>;
 
     fn neg(self) -> Self::Output {
         if let Ok(value) = <_ as TryInto<i64>>::try_into(self) {
-            Some(RuntimeValue::integer(-value))
+            Some(integer_or_float(value.checked_neg(), -(value as f64)))
         } else if let Ok(value) = <_ as TryInto<f64>>::try_into(self) {
             Some(RuntimeValue::float(-value))
         } else {
@@ -146,7 +370,10 @@ impl Add for &RuntimeValue {
     fn add(self, rhs: Self) -> Self::Output {
         match self {
             RuntimeValue::Integer(lhs) => match rhs {
-                RuntimeValue::Integer(rhs) => Some(RuntimeValue::Integer(lhs + rhs)),
+                RuntimeValue::Integer(rhs) => Some(integer_or_float(
+                    lhs.checked_add(*rhs),
+                    *lhs as f64 + *rhs as f64,
+                )),
                 RuntimeValue::Float(rhs) => Some(RuntimeValue::Float(*lhs as f64 + rhs)),
                 _ => None,
             },
@@ -172,7 +399,10 @@ impl Sub for &RuntimeValue {
     fn sub(self, rhs: Self) -> Self::Output {
         match self {
             RuntimeValue::Integer(lhs) => match rhs {
-                RuntimeValue::Integer(rhs) => Some(RuntimeValue::Integer(lhs - rhs)),
+                RuntimeValue::Integer(rhs) => Some(integer_or_float(
+                    lhs.checked_sub(*rhs),
+                    *lhs as f64 - *rhs as f64,
+                )),
                 RuntimeValue::Float(rhs) => Some(RuntimeValue::Float(*lhs as f64 - rhs)),
                 _ => None,
             },
@@ -192,7 +422,10 @@ impl Mul for &RuntimeValue {
     fn mul(self, rhs: Self) -> Self::Output {
         match self {
             RuntimeValue::Integer(lhs) => match rhs {
-                RuntimeValue::Integer(rhs) => Some(RuntimeValue::Integer(lhs * rhs)),
+                RuntimeValue::Integer(rhs) => Some(integer_or_float(
+                    lhs.checked_mul(*rhs),
+                    *lhs as f64 * *rhs as f64,
+                )),
                 RuntimeValue::Float(rhs) => Some(RuntimeValue::Float(*lhs as f64 * rhs)),
                 _ => None,
             },
@@ -215,11 +448,15 @@ impl Div for &RuntimeValue {
                 RuntimeValue::Integer(rhs) => {
                     let result = *lhs as f64 / *rhs as f64;
 
-                    Some(if result.fract() == 0.0 {
-                        RuntimeValue::Integer(result as i64)
-                    } else {
-                        RuntimeValue::Float(result)
-                    })
+                    Some(
+                        if result.fract() == 0.0
+                            && (i64::MIN as f64..i64::MAX as f64).contains(&result)
+                        {
+                            RuntimeValue::Integer(result as i64)
+                        } else {
+                            RuntimeValue::Float(result)
+                        },
+                    )
                 }
                 RuntimeValue::Float(rhs) => Some(RuntimeValue::Float(*lhs as f64 / rhs)),
                 _ => None,
@@ -255,11 +492,21 @@ impl PartialEq for RuntimeValue {
                 RuntimeValue::Boolean(rhs) => lhs == rhs,
                 _ => false,
             },
-            RuntimeValue::Nil => match other {
-                RuntimeValue::Nil => true,
+            RuntimeValue::Nil => matches!(other, RuntimeValue::Nil),
+            RuntimeValue::Callable { .. } => false,
+            RuntimeValue::Class { .. }
+            | RuntimeValue::Instance { .. }
+            | RuntimeValue::List(_)
+            | RuntimeValue::Map(_)
+            | RuntimeValue::Module { .. } => std::ptr::eq(self, other),
+            RuntimeValue::Native(lhs) => match other {
+                RuntimeValue::Native(rhs) => Rc::ptr_eq(lhs, rhs),
+                _ => false,
+            },
+            RuntimeValue::Foreign(lhs) => match other {
+                RuntimeValue::Foreign(rhs) => Rc::ptr_eq(lhs, rhs),
                 _ => false,
             },
-            RuntimeValue::Callable { .. } => false,
         }
     }
 }
@@ -291,6 +538,26 @@ impl fmt::Display for RuntimeValue {
             RuntimeValue::String(value) => write!(f, "{value}"),
             RuntimeValue::Nil => write!(f, "nil"),
             RuntimeValue::Callable { .. } => write!(f, "[callable]"),
+            RuntimeValue::Class { identifier, .. } => write!(f, "[class {identifier}]"),
+            RuntimeValue::Instance { class, .. } => match class.as_ref() {
+                RuntimeValue::Class { identifier, .. } => write!(f, "[{identifier} instance]"),
+                _ => unreachable!(),
+            },
+            RuntimeValue::Native(function) => write!(f, "[native {}]", function.name()),
+            RuntimeValue::List(values) => list::display(
+                f,
+                self as *const Self as *const (),
+                values.borrow().iter(),
+                |f, value| value.display_nested(f),
+            ),
+            RuntimeValue::Map(entries) => map::display(
+                f,
+                self as *const Self as *const (),
+                &entries.borrow(),
+                |f, value| value.display_nested(f),
+            ),
+            RuntimeValue::Module { name, .. } => write!(f, "[module {name}]"),
+            RuntimeValue::Foreign(value) => write!(f, "{value}"),
         }
     }
 }
@@ -0,0 +1,149 @@
+use std::{fs, process};
+
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{
+    runtime::{capabilities::Capabilities, io::SharedBuffer},
+    Runtime,
+};
+
+/// Runs `source`, returning the name and message of the error it stops with.
+fn run(runtime: &Runtime, source: &str) -> Result<(), (&'static str, String)> {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+    Resolver::new().run(&program).unwrap();
+
+    runtime.run(&program).map_err(|error| {
+        let error = error.source();
+        (error.name(), error.to_string())
+    })
+}
+
+#[test]
+fn natives_are_denied_by_default() {
+    let runtime = Runtime::with_output(std::io::sink());
+
+    for (call, message) in [
+        (
+            "readFile(\"Cargo.toml\");",
+            "`readFile` needs the `fs` capability, which was not granted.",
+        ),
+        (
+            "exists(\"Cargo.toml\");",
+            "`exists` needs the `fs` capability, which was not granted.",
+        ),
+        (
+            "env(\"HOME\");",
+            "`env` needs the `env` capability, which was not granted.",
+        ),
+        (
+            "args();",
+            "`args` needs the `env` capability, which was not granted.",
+        ),
+        (
+            "readLine();",
+            "`readLine` needs the `input` capability, which was not granted.",
+        ),
+        (
+            "exit(3);",
+            "`exit` needs the `exit` capability, which was not granted.",
+        ),
+    ] {
+        assert_eq!(
+            run(&runtime, call),
+            Err(("PermissionDenied", message.to_string()))
+        );
+    }
+}
+
+#[test]
+fn denials_can_be_caught() {
+    let output = SharedBuffer::new();
+    let runtime = Runtime::with_output(output.clone());
+
+    run(
+        &runtime,
+        "try { readFile(\"Cargo.toml\"); } catch (e) { print e.message; }",
+    )
+    .unwrap();
+    assert_eq!(
+        output.contents(),
+        "`readFile` needs the `fs` capability, which was not granted.\n"
+    );
+}
+
+#[test]
+fn granted_capabilities_are_allowed() {
+    let runtime = Runtime::with_output(std::io::sink());
+    runtime.set_capabilities(Capabilities {
+        fs: true,
+        ..Capabilities::default()
+    });
+
+    run(&runtime, "assert(exists(\"Cargo.toml\"), \"manifest\");").unwrap();
+    assert_eq!(
+        run(&runtime, "env(\"HOME\");").unwrap_err().0,
+        "PermissionDenied"
+    );
+}
+
+#[test]
+fn imports_outside_exposed_directories_need_fs() {
+    let directory = std::env::temp_dir().join(format!("lox-capabilities-{}", process::id()));
+    let exposed = directory.join("exposed");
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&exposed).unwrap();
+    fs::write(exposed.join("inside.lox"), "export var value = 1;").unwrap();
+    fs::write(directory.join("secret.lox"), "var = \"secret contents\";").unwrap();
+
+    let runtime = Runtime::with_output(std::io::sink());
+    runtime.modules().add_search_path(&exposed);
+
+    run(&runtime, "import \"inside.lox\" as inside;").unwrap();
+
+    // reaching out of the search directory, without revealing what the file holds
+    let (name, message) = run(&runtime, "import \"../secret.lox\" as secret;").unwrap_err();
+    assert_eq!(name, "PermissionDenied");
+    assert_eq!(
+        message,
+        "`import` needs the `fs` capability, which was not granted."
+    );
+
+    // files outside are not looked at, so whether they exist makes no difference
+    for specifier in ["../missing.lox", "../exposed/../secret.lox", "/etc/passwd"] {
+        assert_eq!(
+            run(&runtime, &format!("import \"{specifier}\" as outside;"))
+                .unwrap_err()
+                .0,
+            "PermissionDenied",
+            "{specifier}"
+        );
+    }
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(directory.join("secret.lox"), exposed.join("link.lox")).unwrap();
+        assert_eq!(
+            run(&runtime, "import \"link.lox\" as link;").unwrap_err().0,
+            "PermissionDenied"
+        );
+    }
+    assert_eq!(
+        run(&runtime, "import \"missing.lox\" as missing;")
+            .unwrap_err()
+            .0,
+        "ModuleNotFound"
+    );
+
+    runtime.set_capabilities(Capabilities {
+        fs: true,
+        ..Capabilities::default()
+    });
+    let (name, _) = run(&runtime, "import \"../secret.lox\" as secret;").unwrap_err();
+    assert_eq!(name, "InvalidModule");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
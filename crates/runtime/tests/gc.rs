@@ -0,0 +1,75 @@
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::Runtime;
+
+fn run(runtime: &Runtime, source: &str) {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+    Resolver::new().run(&program).unwrap();
+
+    runtime.run(&program).unwrap();
+}
+
+/// Creates `count` garbage cycles of the kind built by `body`, then forgets them.
+fn leak(runtime: &Runtime, body: &str, count: usize) {
+    run(
+        runtime,
+        &format!(
+            "{{ fun cycle() {{ {body} }} for (var i = 0; i < {count}; i = i + 1) {{ cycle(); }} }}"
+        ),
+    );
+}
+
+#[test]
+fn cycles_are_reclaimed() {
+    let cycles = [
+        // list containing itself
+        "var a = []; a.push(a);",
+        // lists and maps referring to each other
+        "var a = []; var m = {\"a\": a}; a.push(m);",
+        // instance holding itself in a field
+        "class Node {} var node = Node(); node.next = node;",
+        // function closing over the environment that holds it
+        "fun inner() { return inner; }",
+    ];
+
+    for body in cycles {
+        let runtime = Runtime::with_output(std::io::sink());
+        runtime.collect_garbage();
+        let baseline = runtime.memory_usage();
+
+        leak(&runtime, body, 1000);
+        runtime.collect_garbage();
+
+        let stats = runtime.gc_stats();
+        assert!(stats.collected >= 1000, "{body}: collected {stats:?}");
+        assert_eq!(runtime.memory_usage(), baseline, "{body}");
+    }
+}
+
+#[test]
+fn reachable_cycles_are_kept() {
+    let runtime = Runtime::with_output(std::io::sink());
+
+    run(&runtime, "var a = [1]; a.push(a); var b = a;");
+    runtime.collect_garbage();
+
+    let a = runtime.get_global("a").unwrap();
+    assert_eq!(a.to_string(), "[1, [...]]");
+}
+
+#[test]
+fn collections_run_as_the_heap_grows() {
+    let runtime = Runtime::with_output(std::io::sink());
+
+    leak(&runtime, "var a = []; a.push(a);", 10_000);
+
+    let stats = runtime.gc_stats();
+    assert!(stats.collections > 0);
+    // without collections every list would still be tracked
+    assert!(stats.objects < 10_000, "{stats:?}");
+}
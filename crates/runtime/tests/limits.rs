@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{runtime::limits::Limits, Runtime};
+
+/// Runs `source` from a fresh budget, returning the name and message of the error
+/// it stops with.
+fn run(runtime: &Runtime, source: &str) -> Result<(), (&'static str, String)> {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+    Resolver::new().run(&program).unwrap();
+
+    runtime.reset_usage();
+    runtime.run(&program).map_err(|error| {
+        let error = error.source();
+        (error.name(), error.to_string())
+    })
+}
+
+fn limited(limits: Limits) -> Runtime {
+    let runtime = Runtime::with_output(std::io::sink());
+    runtime.set_limits(limits);
+    runtime
+}
+
+#[test]
+fn step_budget_stops_endless_loop() {
+    let runtime = limited(Limits {
+        max_steps: Some(10_000),
+        ..Limits::default()
+    });
+
+    let (name, message) = run(&runtime, "while (true) {}").unwrap_err();
+    assert_eq!(name, "BudgetExhausted");
+    assert_eq!(message, "Execution budget of 10000 steps exhausted.");
+}
+
+#[test]
+fn step_budget_restarts_with_each_run() {
+    let runtime = limited(Limits {
+        max_steps: Some(10_000),
+        ..Limits::default()
+    });
+
+    for _ in 0..3 {
+        run(&runtime, "for (var i = 0; i < 500; i = i + 1) {}").unwrap();
+    }
+}
+
+#[test]
+fn timeout_stops_endless_loop() {
+    let runtime = limited(Limits {
+        timeout: Some(Duration::from_millis(50)),
+        ..Limits::default()
+    });
+
+    let started = Instant::now();
+    let (name, _) = run(&runtime, "while (true) {}").unwrap_err();
+
+    assert_eq!(name, "Timeout");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn call_depth_is_bounded() {
+    let runtime = limited(Limits {
+        max_call_depth: 64,
+        ..Limits::default()
+    });
+
+    run(
+        &runtime,
+        "fun down(n) { if (n > 0) { down(n - 1); } } down(60);",
+    )
+    .unwrap();
+
+    let (name, message) = run(&runtime, "fun f() { f(); } f();").unwrap_err();
+    assert_eq!(name, "StackOverflow");
+    assert_eq!(message, "Maximum call depth of 64 exceeded.");
+}
+
+#[test]
+fn deep_recursion_within_default_depth_does_not_overflow() {
+    let runtime = limited(Limits::default());
+
+    run(
+        &runtime,
+        "fun down(n) { if (n > 0) { down(n - 1); } } down(4000);",
+    )
+    .unwrap();
+
+    let (name, _) = run(&runtime, "fun f() { f(); } f();").unwrap_err();
+    assert_eq!(name, "StackOverflow");
+}
+
+#[test]
+fn exhausted_budget_cannot_be_caught() {
+    let runtime = limited(Limits {
+        max_steps: Some(1_000),
+        ..Limits::default()
+    });
+
+    let (name, _) = run(&runtime, "try { while (true) {} } catch (e) {}").unwrap_err();
+    assert_eq!(name, "BudgetExhausted");
+}
+
+#[test]
+fn memory_limit_stops_unbounded_growth() {
+    let runtime = limited(Limits {
+        max_memory: Some(1 << 20),
+        ..Limits::default()
+    });
+
+    let (name, message) = run(
+        &runtime,
+        "var a = []; while (true) { a.push(\"item \" + str(len(a))); }",
+    )
+    .unwrap_err();
+    assert_eq!(name, "OutOfMemory");
+    assert_eq!(message, "Memory limit of 1048576 bytes exceeded.");
+}
+
+#[test]
+fn ensuring_memory_is_available_does_not_charge_for_it() {
+    let runtime = limited(Limits {
+        max_memory: Some(1 << 20),
+        ..Limits::default()
+    });
+
+    runtime.ensure_available(1 << 19).unwrap();
+    runtime.ensure_available(1 << 19).unwrap();
+    assert!(runtime.ensure_available(2 << 20).is_err());
+}
+
+/// Peak resident memory of the test process, where the platform reports it.
+fn peak_memory() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+
+    Some(kilobytes * 1024)
+}
+
+#[test]
+fn oversized_repeat_fails_before_it_is_built() {
+    let runtime = limited(Limits {
+        max_memory: Some(1 << 20),
+        ..Limits::default()
+    });
+
+    let (name, _) = run(&runtime, "var s = \"ab\".repeat(1000000000);").unwrap_err();
+    assert_eq!(name, "OutOfMemory");
+
+    // a built string would have taken 2 GB
+    if let Some(peak) = peak_memory() {
+        assert!(peak < 512 << 20, "peak memory of {peak} bytes");
+    }
+
+    run(&runtime, "var s = \"ab\".repeat(1000);").unwrap();
+}
+
+#[test]
+fn unallocatable_repeat_is_an_error_without_a_limit() {
+    let runtime = limited(Limits::default());
+
+    for count in ["4611686018427387904", "2305843009213693951"] {
+        let (name, _) = run(&runtime, &format!("\"ab\".repeat({count});")).unwrap_err();
+        assert_eq!(name, "AllocationFailed");
+    }
+}
@@ -0,0 +1,65 @@
+use std::{fs, path::PathBuf, process};
+
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{error::RuntimeResult, Runtime};
+
+/// Empty directory for the modules of one test.
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("lox-{name}-{}", process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn run(runtime: &Runtime, source: &str) -> RuntimeResult<()> {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+    Resolver::new().run(&program).unwrap();
+
+    runtime.run(&program)
+}
+
+#[test]
+fn errors_in_modules_show_the_source_that_ran() {
+    let directory = directory("module-source");
+    let module = directory.join("divide.lox");
+    fs::write(&module, "export fun divide(a) {\n  return a / 0;\n}\n").unwrap();
+
+    let runtime = Runtime::with_output(std::io::sink());
+    runtime.modules().add_search_path(&directory);
+
+    let source = "import \"divide.lox\" as divide;\ndivide.divide(1);";
+    let error = run(&runtime, source).unwrap_err();
+
+    // the module changing afterwards does not affect how the error is shown
+    fs::write(&module, "// rewritten\n").unwrap();
+    let diagnostic = error.diagnostic(source).to_string();
+
+    assert!(diagnostic.contains("divide.lox:2:"), "{diagnostic}");
+    assert!(diagnostic.contains("return a / 0;"), "{diagnostic}");
+    assert!(!diagnostic.contains("rewritten"), "{diagnostic}");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn modules_that_fail_to_compile_point_into_their_file() {
+    let directory = directory("module-invalid");
+    fs::write(directory.join("broken.lox"), "var a = 1;\nvar = 2;\n").unwrap();
+
+    let runtime = Runtime::with_output(std::io::sink());
+    runtime.modules().add_search_path(&directory);
+
+    let source = "import \"broken.lox\" as broken;";
+    let error = run(&runtime, source).unwrap_err();
+    fs::remove_dir_all(&directory).unwrap();
+
+    let diagnostic = error.diagnostic(source).to_string();
+    assert!(diagnostic.contains("broken.lox:2:"), "{diagnostic}");
+    assert!(diagnostic.contains("var = 2;"), "{diagnostic}");
+}
@@ -0,0 +1,122 @@
+use error::span::Span;
+
+use crate::value::Value;
+
+/// A single VM instruction. Operands index into the chunk's constant pool,
+/// the current frame's locals or upvalues, or are relative jump distances.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpCode {
+    Constant(u16),
+    Nil,
+    True,
+    False,
+    Pop,
+    GetLocal(u8),
+    SetLocal(u8),
+    GetGlobal(u16),
+    DefineGlobal(u16),
+    SetGlobal(u16),
+    GetUpvalue(u8),
+    SetUpvalue(u8),
+    GetProperty(u16),
+    SetProperty(u16),
+    GetSuper(u16),
+    Equal,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Not,
+    Negate,
+    Print,
+    Jump(u16),
+    JumpIfFalse(u16),
+    Loop(u16),
+    Call(u8),
+    Closure(u16),
+    CloseUpvalue,
+    Return,
+    Class(u16),
+    Inherit,
+    Method(u16),
+    BuildList(u16),
+    /// Concatenates the given number of values on the stack into a string.
+    Interpolate(u16),
+    /// Builds a map from the given number of key and value pairs on the stack.
+    BuildMap(u16),
+    GetIndex,
+    SetIndex,
+    Slice,
+    /// Enters a `try` statement whose handler starts the given distance ahead.
+    PushHandler(u16),
+    PopHandler,
+    Throw,
+    /// Throws the exception a `finally` block ran for, held in the given local,
+    /// reported like it first was.
+    Rethrow(u8),
+    /// Pushes the module at the path held in the given constant, running it first
+    /// unless it was already imported.
+    Import(u16),
+}
+
+/// Compiled bytecode of a single function.
+#[derive(Debug, Default)]
+pub struct Chunk {
+    code: Vec<OpCode>,
+    constants: Vec<Value>,
+    /// Run-length encoded `(first instruction offset, span)` pairs.
+    spans: Vec<(usize, Span)>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, op: OpCode, span: Span) -> usize {
+        if self
+            .spans
+            .last()
+            .map(|&(_, last)| last != span)
+            .unwrap_or(true)
+        {
+            self.spans.push((self.code.len(), span));
+        }
+
+        self.code.push(op);
+        self.code.len() - 1
+    }
+
+    pub fn add_constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    pub fn code(&self) -> &[OpCode] {
+        &self.code
+    }
+
+    pub fn code_mut(&mut self) -> &mut [OpCode] {
+        &mut self.code
+    }
+
+    pub fn constant(&self, index: usize) -> &Value {
+        &self.constants[index]
+    }
+
+    /// Source span of the instruction at `offset`.
+    pub fn span(&self, offset: usize) -> Span {
+        match self
+            .spans
+            .binary_search_by(|&(start, _)| start.cmp(&offset))
+        {
+            Ok(index) => self.spans[index].1,
+            Err(0) => Span::default(),
+            Err(index) => self.spans[index - 1].1,
+        }
+    }
+}
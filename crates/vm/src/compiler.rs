@@ -0,0 +1,1031 @@
+use std::rc::Rc;
+
+use ::error::InterpreterError;
+use ast::{
+    expression::{Expression, ExpressionKind},
+    literal::Literal,
+    operator::Operator,
+    statement::{Statement, StatementKind},
+};
+use error::span::Span;
+use runtime::runtime::module::ModuleFile;
+
+use crate::{
+    chunk::OpCode,
+    error::{CompilerError, CompilerErrorKind, CompilerResult},
+    value::{Function, UpvalueDescriptor, Value},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionKind {
+    Script,
+    Function,
+    Method,
+}
+
+#[derive(Debug)]
+struct Local {
+    name: String,
+    depth: usize,
+    captured: bool,
+}
+
+#[derive(Debug)]
+struct Loop {
+    /// Number of locals alive when the loop body was entered.
+    locals: usize,
+    /// Start of the condition for `while`; `None` for `for`, whose `continue`
+    /// jumps forward to the increment clause.
+    continue_target: Option<usize>,
+    continues: Vec<usize>,
+    breaks: Vec<usize>,
+}
+
+/// Code protected by an exception handler, which jumps out of it have to unregister.
+#[derive(Debug, Clone)]
+struct Try {
+    /// Number of loops entered when the protected code started.
+    loops: usize,
+    /// Number of locals in scope for the `finally` block.
+    locals: usize,
+    /// Block to run on the way out.
+    finally: Option<Rc<Statement>>,
+}
+
+#[derive(Debug)]
+struct FunctionState {
+    function: Function,
+    locals: Vec<Local>,
+    upvalues: Vec<UpvalueDescriptor>,
+    loops: Vec<Loop>,
+    tries: Vec<Try>,
+    scope_depth: usize,
+}
+
+impl FunctionState {
+    fn new(name: String, kind: FunctionKind, file: Option<Rc<ModuleFile>>) -> Self {
+        // slot zero holds the callee, or the receiver for methods
+        let reserved = Local {
+            name: if kind == FunctionKind::Method {
+                "this".to_string()
+            } else {
+                String::new()
+            },
+            depth: 0,
+            captured: false,
+        };
+
+        Self {
+            function: Function {
+                name,
+                file,
+                ..Function::default()
+            },
+            locals: vec![reserved],
+            upvalues: vec![],
+            loops: vec![],
+            tries: vec![],
+            scope_depth: 0,
+        }
+    }
+}
+
+/// Compiles a resolved program into bytecode for the [`crate::Vm`].
+#[derive(Debug, Default)]
+pub struct Compiler {
+    states: Vec<FunctionState>,
+    /// Span of the node being compiled, recorded for every emitted instruction.
+    span: Span,
+    /// Module being compiled, `None` for the main program.
+    file: Option<Rc<ModuleFile>>,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compiler for the module read from `file`, whose errors point into it.
+    pub fn with_file(file: Rc<ModuleFile>) -> Self {
+        Self {
+            file: Some(file),
+            ..Self::default()
+        }
+    }
+
+    pub fn compile(mut self, program: &[Rc<Statement>]) -> CompilerResult<Rc<Function>> {
+        self.states.push(FunctionState::new(
+            String::new(),
+            FunctionKind::Script,
+            self.file.clone(),
+        ));
+
+        for stmt in program {
+            self.statement(stmt)?;
+        }
+
+        self.emit(OpCode::Nil);
+        self.emit(OpCode::Return);
+
+        Ok(Rc::new(self.states.pop().unwrap().function))
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> CompilerResult<()> {
+        let enclosing = std::mem::replace(&mut self.span, stmt.span);
+        self.statement_kind(&stmt.kind)?;
+        self.span = enclosing;
+
+        Ok(())
+    }
+
+    fn statement_kind(&mut self, kind: &StatementKind) -> CompilerResult<()> {
+        match kind {
+            StatementKind::Print(expr) => {
+                self.expression(expr)?;
+                self.emit(OpCode::Print);
+            }
+            StatementKind::Expression(expr) => {
+                self.expression(expr)?;
+                self.emit(OpCode::Pop);
+            }
+            StatementKind::VariableDeclaration {
+                identifier,
+                expression,
+            } => {
+                self.expression(expression)?;
+                self.define_variable(identifier)?;
+            }
+            StatementKind::FunctionDeclaration {
+                identifier,
+                parameters,
+                execute,
+            } => {
+                // declared up front so the function can refer to itself
+                if self.state().scope_depth > 0 {
+                    self.add_local(identifier)?;
+                }
+
+                self.function(identifier, parameters, execute, FunctionKind::Function)?;
+
+                if self.state().scope_depth == 0 {
+                    let name = self.identifier_constant(identifier)?;
+                    self.emit(OpCode::DefineGlobal(name));
+                }
+            }
+            StatementKind::ClassDeclaration {
+                identifier,
+                superclass,
+                methods,
+            } => self.class(identifier, superclass.as_ref(), methods)?,
+            StatementKind::Block(statements) => {
+                self.begin_scope();
+
+                for stmt in statements {
+                    self.statement(stmt)?;
+                }
+
+                self.end_scope();
+            }
+            StatementKind::Conditional {
+                condition,
+                then,
+                alternative,
+            } => {
+                self.expression(condition)?;
+                let then_jump = self.emit(OpCode::JumpIfFalse(0));
+                self.emit(OpCode::Pop);
+                self.statement(then)?;
+
+                let else_jump = self.emit(OpCode::Jump(0));
+                self.patch_jump(then_jump)?;
+                self.emit(OpCode::Pop);
+
+                if let Some(alternative) = alternative {
+                    self.statement(alternative)?;
+                }
+
+                self.patch_jump(else_jump)?;
+            }
+            StatementKind::While { condition, block } => {
+                let loop_start = self.code_len();
+                self.expression(condition)?;
+
+                let exit_jump = self.emit(OpCode::JumpIfFalse(0));
+                self.emit(OpCode::Pop);
+
+                self.loop_body(block, Some(loop_start))?;
+
+                self.emit_loop(loop_start)?;
+                self.patch_jump(exit_jump)?;
+                self.emit(OpCode::Pop);
+                self.end_loop()?;
+            }
+            StatementKind::For {
+                initializer,
+                condition,
+                increment,
+                block,
+            } => {
+                self.begin_scope();
+
+                if let Some(initializer) = initializer {
+                    self.statement(initializer)?;
+                }
+
+                let loop_start = self.code_len();
+                let exit_jump = match condition {
+                    Some(condition) => {
+                        self.expression(condition)?;
+                        let exit_jump = self.emit(OpCode::JumpIfFalse(0));
+                        self.emit(OpCode::Pop);
+                        Some(exit_jump)
+                    }
+                    None => None,
+                };
+
+                self.loop_body(block, None)?;
+
+                let continues = std::mem::take(&mut self.current_loop().continues);
+                for jump in continues {
+                    self.patch_jump(jump)?;
+                }
+
+                if let Some(increment) = increment {
+                    self.expression(increment)?;
+                    self.emit(OpCode::Pop);
+                }
+
+                self.emit_loop(loop_start)?;
+
+                if let Some(exit_jump) = exit_jump {
+                    self.patch_jump(exit_jump)?;
+                    self.emit(OpCode::Pop);
+                }
+
+                self.end_loop()?;
+                self.end_scope();
+            }
+            StatementKind::Break => {
+                self.exit_tries(self.loop_tries())?;
+                self.discard_loop_locals();
+                let jump = self.emit(OpCode::Jump(0));
+                self.current_loop().breaks.push(jump);
+            }
+            StatementKind::Continue => {
+                self.exit_tries(self.loop_tries())?;
+                self.discard_loop_locals();
+
+                match self.current_loop().continue_target {
+                    Some(target) => self.emit_loop(target)?,
+                    None => {
+                        let jump = self.emit(OpCode::Jump(0));
+                        self.current_loop().continues.push(jump);
+                    }
+                }
+            }
+            StatementKind::Return(expression) => {
+                self.expression(expression)?;
+
+                if self.state().tries.is_empty() {
+                    self.emit(OpCode::Return);
+                } else {
+                    // kept as a local so `finally` blocks find their variables in place
+                    self.begin_scope();
+                    self.add_local("")?;
+                    let slot = (self.state().locals.len() - 1) as u8;
+
+                    self.exit_tries(0)?;
+                    self.emit(OpCode::GetLocal(slot));
+                    self.emit(OpCode::Return);
+                    self.end_scope();
+                }
+            }
+            StatementKind::Throw(expression) => {
+                self.expression(expression)?;
+                self.emit(OpCode::Throw);
+            }
+            StatementKind::Try {
+                block,
+                catch,
+                finally,
+            } => self.try_stmt(
+                block,
+                catch
+                    .as_ref()
+                    .map(|(identifier, handler)| (identifier.as_str(), handler.as_ref())),
+                finally.as_ref(),
+            )?,
+            StatementKind::Import { path, alias } => {
+                let path = self.make_constant(Value::String(path.as_str().into()))?;
+                self.emit(OpCode::Import(path));
+                self.define_variable(alias)?;
+            }
+            StatementKind::ImportFrom { path, names } => {
+                let path = self.make_constant(Value::String(path.as_str().into()))?;
+
+                for name in names {
+                    self.emit(OpCode::Import(path));
+                    let property = self.identifier_constant(name)?;
+                    self.emit(OpCode::GetProperty(property));
+                    self.define_variable(name)?;
+                }
+            }
+            StatementKind::Export(declaration) => self.statement(declaration)?,
+        }
+
+        Ok(())
+    }
+
+    fn try_stmt(
+        &mut self,
+        block: &Statement,
+        catch: Option<(&str, &Statement)>,
+        finally: Option<&Rc<Statement>>,
+    ) -> CompilerResult<()> {
+        let locals = self.state().locals.len();
+        let handler = self.protect(block, finally, locals)?;
+        if let Some(finally) = finally {
+            self.statement(finally)?;
+        }
+        let mut exits = vec![self.emit(OpCode::Jump(0))];
+
+        self.patch_jump(handler)?;
+
+        if let Some((identifier, catch)) = catch {
+            // the caught value is on top of the stack, where the local lives
+            self.begin_scope();
+            self.add_local(identifier)?;
+
+            match finally {
+                Some(finally) => {
+                    let handler = self.protect(catch, Some(finally), locals)?;
+                    self.end_scope();
+                    self.statement(finally)?;
+                    exits.push(self.emit(OpCode::Jump(0)));
+
+                    // the caught value is still below the one thrown by the handler
+                    self.patch_jump(handler)?;
+                    self.begin_scope();
+                    self.add_local("")?;
+                    self.rethrow_after(finally)?;
+                    self.end_scope();
+                }
+                None => {
+                    self.statement(catch)?;
+                    self.end_scope();
+                }
+            }
+        } else if let Some(finally) = finally {
+            self.rethrow_after(finally)?;
+        }
+
+        for jump in exits {
+            self.patch_jump(jump)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compiles `block` under a new exception handler and returns the handler's
+    /// jump to patch once the handling code starts.
+    fn protect(
+        &mut self,
+        block: &Statement,
+        finally: Option<&Rc<Statement>>,
+        locals: usize,
+    ) -> CompilerResult<usize> {
+        let handler = self.emit(OpCode::PushHandler(0));
+
+        let loops = self.state().loops.len();
+        self.state_mut().tries.push(Try {
+            loops,
+            locals,
+            finally: finally.cloned(),
+        });
+        self.statement(block)?;
+        self.state_mut().tries.pop();
+
+        self.emit(OpCode::PopHandler);
+
+        Ok(handler)
+    }
+
+    /// Runs `finally` for the exception on top of the stack and throws it again.
+    fn rethrow_after(&mut self, finally: &Statement) -> CompilerResult<()> {
+        self.begin_scope();
+        self.add_local("")?;
+        let slot = (self.state().locals.len() - 1) as u8;
+
+        self.statement(finally)?;
+        self.emit(OpCode::Rethrow(slot));
+        self.end_scope();
+
+        Ok(())
+    }
+
+    /// Number of enclosing `try` statements entered outside the innermost loop.
+    fn loop_tries(&self) -> usize {
+        let loops = self.state().loops.len();
+
+        self.state()
+            .tries
+            .iter()
+            .take_while(|entry| entry.loops < loops)
+            .count()
+    }
+
+    /// Unregisters the handlers of the `try` statements entered after the first
+    /// `depth` ones, innermost first, running their `finally` blocks.
+    fn exit_tries(&mut self, depth: usize) -> CompilerResult<()> {
+        for index in (depth..self.state().tries.len()).rev() {
+            self.emit(OpCode::PopHandler);
+
+            // a jump out of the `finally` block itself only exits the enclosing statements
+            let exited = self.state_mut().tries.split_off(index);
+            let Try {
+                locals, finally, ..
+            } = exited[0].clone();
+
+            // locals declared inside the statement are still on the stack but out of scope
+            let hidden = self.state_mut().locals[locals..]
+                .iter_mut()
+                .map(|local| std::mem::take(&mut local.name))
+                .collect::<Vec<_>>();
+
+            let result = match finally {
+                Some(finally) => self.statement(&finally),
+                None => Ok(()),
+            };
+
+            for (local, name) in self.state_mut().locals[locals..].iter_mut().zip(hidden) {
+                local.name = name;
+            }
+            self.state_mut().tries.extend(exited);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn loop_body(
+        &mut self,
+        block: &Statement,
+        continue_target: Option<usize>,
+    ) -> CompilerResult<()> {
+        let locals = self.state().locals.len();
+        self.state_mut().loops.push(Loop {
+            locals,
+            continue_target,
+            continues: vec![],
+            breaks: vec![],
+        });
+
+        self.statement(block)
+    }
+
+    fn end_loop(&mut self) -> CompilerResult<()> {
+        let finished = self.state_mut().loops.pop().unwrap();
+
+        for jump in finished.breaks {
+            self.patch_jump(jump)?;
+        }
+
+        Ok(())
+    }
+
+    fn current_loop(&mut self) -> &mut Loop {
+        self.state_mut().loops.last_mut().unwrap()
+    }
+
+    /// Pops the locals declared inside the innermost loop without forgetting them,
+    /// since compilation of the enclosing block continues after `break`/`continue`.
+    fn discard_loop_locals(&mut self) {
+        let locals = self.state().loops.last().unwrap().locals;
+        let captured = self.state().locals[locals..]
+            .iter()
+            .rev()
+            .map(|local| local.captured)
+            .collect::<Vec<_>>();
+
+        for captured in captured {
+            self.emit(if captured {
+                OpCode::CloseUpvalue
+            } else {
+                OpCode::Pop
+            });
+        }
+    }
+
+    fn class(
+        &mut self,
+        identifier: &str,
+        superclass: Option<&Expression>,
+        methods: &[Rc<Statement>],
+    ) -> CompilerResult<()> {
+        let name = self.identifier_constant(identifier)?;
+
+        if self.state().scope_depth > 0 {
+            self.add_local(identifier)?;
+        }
+
+        self.emit(OpCode::Class(name));
+
+        if self.state().scope_depth == 0 {
+            self.emit(OpCode::DefineGlobal(name));
+        }
+
+        if let Some(superclass) = superclass {
+            self.expression(superclass)?;
+
+            // the superclass stays on the stack as a local that methods capture
+            self.begin_scope();
+            self.add_local("super")?;
+
+            self.named_variable(identifier)?;
+            self.emit(OpCode::Inherit);
+        }
+
+        self.named_variable(identifier)?;
+
+        for method in methods {
+            if let StatementKind::FunctionDeclaration {
+                identifier,
+                parameters,
+                execute,
+            } = &method.kind
+            {
+                self.function(identifier, parameters, execute, FunctionKind::Method)?;
+                let name = self.identifier_constant(identifier)?;
+                self.emit(OpCode::Method(name));
+            }
+        }
+
+        self.emit(OpCode::Pop);
+
+        if superclass.is_some() {
+            self.end_scope();
+        }
+
+        Ok(())
+    }
+
+    fn function(
+        &mut self,
+        identifier: &str,
+        parameters: &[String],
+        execute: &Statement,
+        kind: FunctionKind,
+    ) -> CompilerResult<()> {
+        self.states.push(FunctionState::new(
+            identifier.to_string(),
+            kind,
+            self.file.clone(),
+        ));
+        self.begin_scope();
+
+        self.state_mut().function.arity = parameters.len();
+        for parameter in parameters {
+            self.add_local(parameter)?;
+        }
+
+        match &execute.kind {
+            StatementKind::Block(statements) => {
+                for stmt in statements {
+                    self.statement(stmt)?;
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        self.emit(OpCode::Nil);
+        self.emit(OpCode::Return);
+
+        let state = self.states.pop().unwrap();
+        let function = Function {
+            upvalues: state.upvalues,
+            ..state.function
+        };
+
+        let constant = self.make_constant(Value::Function(Rc::new(function)))?;
+        self.emit(OpCode::Closure(constant));
+
+        Ok(())
+    }
+
+    fn expression(&mut self, expr: &Expression) -> CompilerResult<()> {
+        let enclosing = std::mem::replace(&mut self.span, expr.span);
+        self.expression_kind(&expr.kind)?;
+        self.span = enclosing;
+
+        Ok(())
+    }
+
+    fn expression_kind(&mut self, kind: &ExpressionKind) -> CompilerResult<()> {
+        match kind {
+            ExpressionKind::Binary {
+                left,
+                operator,
+                right,
+            } => self.binary(left, operator, right)?,
+            ExpressionKind::Unary { operator, right } => {
+                self.expression(right)?;
+
+                match operator {
+                    Operator::Subtraction => self.emit(OpCode::Negate),
+                    Operator::Negation => self.emit(OpCode::Not),
+                    _ => unreachable!(),
+                };
+            }
+            ExpressionKind::Literal(literal) => {
+                match literal {
+                    Literal::Boolean(true) => self.emit(OpCode::True),
+                    Literal::Boolean(false) => self.emit(OpCode::False),
+                    Literal::Nil => self.emit(OpCode::Nil),
+                    Literal::Integer(value) => self.emit_constant(Value::Integer(*value))?,
+                    Literal::Number(value) => self.emit_constant(Value::number(*value))?,
+                    Literal::String(value) => {
+                        self.emit_constant(Value::String(value.as_str().into()))?
+                    }
+                };
+            }
+            ExpressionKind::Grouping(expr) => self.expression(expr)?,
+            ExpressionKind::Function {
+                parameters,
+                execute,
+            } => self.function("", parameters, execute, FunctionKind::Function)?,
+            ExpressionKind::Conditional {
+                condition,
+                then,
+                alternative,
+            } => {
+                // the ternary only takes the first branch on a literal `true`
+                self.expression(condition)?;
+                self.emit(OpCode::True);
+                self.emit(OpCode::Equal);
+
+                let then_jump = self.emit(OpCode::JumpIfFalse(0));
+                self.emit(OpCode::Pop);
+                self.expression(then)?;
+
+                let else_jump = self.emit(OpCode::Jump(0));
+                self.patch_jump(then_jump)?;
+                self.emit(OpCode::Pop);
+                self.expression(alternative)?;
+
+                self.patch_jump(else_jump)?;
+            }
+            ExpressionKind::Identifier { identifier, .. } => self.named_variable(identifier)?,
+            ExpressionKind::Assignment {
+                identifier,
+                expression,
+                ..
+            } => {
+                self.expression(expression)?;
+                self.set_variable(identifier)?;
+
+                // assignments evaluate to nil
+                self.emit(OpCode::Pop);
+                self.emit(OpCode::Nil);
+            }
+            ExpressionKind::FunctionInvokation { callee, arguments } => {
+                self.expression(callee)?;
+
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+
+                let count = u8::try_from(arguments.len())
+                    .map_err(|_| self.construct_error(CompilerErrorKind::TooManyArguments))?;
+                self.emit(OpCode::Call(count));
+            }
+            ExpressionKind::Get { object, property } => {
+                self.expression(object)?;
+                let name = self.identifier_constant(property)?;
+                self.emit(OpCode::GetProperty(name));
+            }
+            ExpressionKind::Set {
+                object,
+                property,
+                expression,
+            } => {
+                self.expression(object)?;
+                self.expression(expression)?;
+                let name = self.identifier_constant(property)?;
+                self.emit(OpCode::SetProperty(name));
+            }
+            ExpressionKind::List(elements) => {
+                for element in elements {
+                    self.expression(element)?;
+                }
+
+                let count = u16::try_from(elements.len())
+                    .map_err(|_| self.construct_error(CompilerErrorKind::TooManyElements))?;
+                self.emit(OpCode::BuildList(count));
+            }
+            ExpressionKind::Interpolation(parts) => {
+                for part in parts {
+                    self.expression(part)?;
+                }
+
+                let count = u16::try_from(parts.len())
+                    .map_err(|_| self.construct_error(CompilerErrorKind::TooManyElements))?;
+                self.emit(OpCode::Interpolate(count));
+            }
+            ExpressionKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.expression(key)?;
+                    self.expression(value)?;
+                }
+
+                let count = u16::try_from(entries.len())
+                    .map_err(|_| self.construct_error(CompilerErrorKind::TooManyElements))?;
+                self.emit(OpCode::BuildMap(count));
+            }
+            ExpressionKind::Index { object, index } => {
+                self.expression(object)?;
+                self.expression(index)?;
+                self.emit(OpCode::GetIndex);
+            }
+            ExpressionKind::SetIndex {
+                object,
+                index,
+                expression,
+            } => {
+                self.expression(object)?;
+                self.expression(index)?;
+                self.expression(expression)?;
+                self.emit(OpCode::SetIndex);
+            }
+            ExpressionKind::Slice { object, start, end } => {
+                self.expression(object)?;
+
+                // a missing bound is passed as nil
+                for bound in [start, end] {
+                    match bound {
+                        Some(bound) => self.expression(bound)?,
+                        None => {
+                            self.emit(OpCode::Nil);
+                        }
+                    }
+                }
+
+                self.emit(OpCode::Slice);
+            }
+            ExpressionKind::This { .. } => self.named_variable("this")?,
+            ExpressionKind::Super { method, .. } => {
+                self.named_variable("this")?;
+                self.named_variable("super")?;
+                let name = self.identifier_constant(method)?;
+                self.emit(OpCode::GetSuper(name));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        left: &Expression,
+        operator: &Operator,
+        right: &Expression,
+    ) -> CompilerResult<()> {
+        self.expression(left)?;
+
+        match operator {
+            Operator::Conjunction => {
+                let false_jump = self.emit(OpCode::JumpIfFalse(0));
+                self.emit(OpCode::Pop);
+                self.expression(right)?;
+                self.emit(OpCode::Not);
+                self.emit(OpCode::Not);
+
+                let end_jump = self.emit(OpCode::Jump(0));
+                self.patch_jump(false_jump)?;
+                self.emit(OpCode::Pop);
+                self.emit(OpCode::False);
+                self.patch_jump(end_jump)?;
+
+                return Ok(());
+            }
+            Operator::Disjunction => {
+                let false_jump = self.emit(OpCode::JumpIfFalse(0));
+                self.emit(OpCode::Pop);
+                self.emit(OpCode::True);
+
+                let end_jump = self.emit(OpCode::Jump(0));
+                self.patch_jump(false_jump)?;
+                self.emit(OpCode::Pop);
+                self.expression(right)?;
+                self.emit(OpCode::Not);
+                self.emit(OpCode::Not);
+                self.patch_jump(end_jump)?;
+
+                return Ok(());
+            }
+            _ => (),
+        }
+
+        self.expression(right)?;
+
+        match operator {
+            Operator::Addition => self.emit(OpCode::Add),
+            Operator::Subtraction => self.emit(OpCode::Subtract),
+            Operator::Multiplication => self.emit(OpCode::Multiply),
+            Operator::Division => self.emit(OpCode::Divide),
+            Operator::Greater => self.emit(OpCode::Greater),
+            Operator::GreaterOrEqual => self.emit(OpCode::GreaterEqual),
+            Operator::Less => self.emit(OpCode::Less),
+            Operator::LessOrEqual => self.emit(OpCode::LessEqual),
+            Operator::Equal => self.emit(OpCode::Equal),
+            Operator::NotEqual => {
+                self.emit(OpCode::Equal);
+                self.emit(OpCode::Not)
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(())
+    }
+
+    fn define_variable(&mut self, identifier: &str) -> CompilerResult<()> {
+        if self.state().scope_depth > 0 {
+            self.add_local(identifier)
+        } else {
+            let name = self.identifier_constant(identifier)?;
+            self.emit(OpCode::DefineGlobal(name));
+            Ok(())
+        }
+    }
+
+    fn named_variable(&mut self, identifier: &str) -> CompilerResult<()> {
+        let state = self.states.len() - 1;
+
+        if let Some(slot) = self.resolve_local(state, identifier) {
+            self.emit(OpCode::GetLocal(slot));
+        } else if let Some(index) = self.resolve_upvalue(state, identifier)? {
+            self.emit(OpCode::GetUpvalue(index));
+        } else {
+            let name = self.identifier_constant(identifier)?;
+            self.emit(OpCode::GetGlobal(name));
+        }
+
+        Ok(())
+    }
+
+    fn set_variable(&mut self, identifier: &str) -> CompilerResult<()> {
+        let state = self.states.len() - 1;
+
+        if let Some(slot) = self.resolve_local(state, identifier) {
+            self.emit(OpCode::SetLocal(slot));
+        } else if let Some(index) = self.resolve_upvalue(state, identifier)? {
+            self.emit(OpCode::SetUpvalue(index));
+        } else {
+            let name = self.identifier_constant(identifier)?;
+            self.emit(OpCode::SetGlobal(name));
+        }
+
+        Ok(())
+    }
+
+    fn resolve_local(&self, state: usize, identifier: &str) -> Option<u8> {
+        self.states[state]
+            .locals
+            .iter()
+            .rposition(|local| local.name == identifier)
+            .map(|slot| slot as u8)
+    }
+
+    fn resolve_upvalue(&mut self, state: usize, identifier: &str) -> CompilerResult<Option<u8>> {
+        if state == 0 {
+            return Ok(None);
+        }
+
+        if let Some(slot) = self.resolve_local(state - 1, identifier) {
+            self.states[state - 1].locals[slot as usize].captured = true;
+            return self.add_upvalue(state, slot, true).map(Some);
+        }
+
+        match self.resolve_upvalue(state - 1, identifier)? {
+            Some(index) => self.add_upvalue(state, index, false).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn add_upvalue(&mut self, state: usize, index: u8, is_local: bool) -> CompilerResult<u8> {
+        let upvalues = &mut self.states[state].upvalues;
+
+        if let Some(existing) = upvalues
+            .iter()
+            .position(|upvalue| upvalue.index == index && upvalue.is_local == is_local)
+        {
+            return Ok(existing as u8);
+        }
+
+        if upvalues.len() > u8::MAX as usize {
+            return Err(self.construct_error(CompilerErrorKind::TooManyUpvalues));
+        }
+
+        upvalues.push(UpvalueDescriptor { index, is_local });
+        Ok((upvalues.len() - 1) as u8)
+    }
+
+    fn add_local(&mut self, identifier: &str) -> CompilerResult<()> {
+        if self.state().locals.len() > u8::MAX as usize {
+            return Err(self.construct_error(CompilerErrorKind::TooManyLocals));
+        }
+
+        let depth = self.state().scope_depth;
+        self.state_mut().locals.push(Local {
+            name: identifier.to_string(),
+            depth,
+            captured: false,
+        });
+
+        Ok(())
+    }
+
+    fn begin_scope(&mut self) {
+        self.state_mut().scope_depth += 1;
+    }
+
+    fn end_scope(&mut self) {
+        self.state_mut().scope_depth -= 1;
+
+        while let Some(local) = self.state().locals.last() {
+            if local.depth <= self.state().scope_depth {
+                break;
+            }
+
+            let op = if local.captured {
+                OpCode::CloseUpvalue
+            } else {
+                OpCode::Pop
+            };
+
+            self.emit(op);
+            self.state_mut().locals.pop();
+        }
+    }
+
+    fn identifier_constant(&mut self, identifier: &str) -> CompilerResult<u16> {
+        self.make_constant(Value::String(identifier.into()))
+    }
+
+    fn make_constant(&mut self, value: Value) -> CompilerResult<u16> {
+        let index = self.state_mut().function.chunk.add_constant(value);
+
+        u16::try_from(index).map_err(|_| self.construct_error(CompilerErrorKind::TooManyConstants))
+    }
+
+    fn emit_constant(&mut self, value: Value) -> CompilerResult<usize> {
+        let constant = self.make_constant(value)?;
+        Ok(self.emit(OpCode::Constant(constant)))
+    }
+
+    fn emit_loop(&mut self, loop_start: usize) -> CompilerResult<()> {
+        let offset = u16::try_from(self.code_len() - loop_start + 1)
+            .map_err(|_| self.construct_error(CompilerErrorKind::JumpTooLarge))?;
+
+        self.emit(OpCode::Loop(offset));
+
+        Ok(())
+    }
+
+    fn patch_jump(&mut self, jump: usize) -> CompilerResult<()> {
+        let offset = u16::try_from(self.code_len() - jump - 1)
+            .map_err(|_| self.construct_error(CompilerErrorKind::JumpTooLarge))?;
+
+        let code = self.state_mut().function.chunk.code_mut();
+        code[jump] = match code[jump] {
+            OpCode::Jump(_) => OpCode::Jump(offset),
+            OpCode::JumpIfFalse(_) => OpCode::JumpIfFalse(offset),
+            OpCode::PushHandler(_) => OpCode::PushHandler(offset),
+            _ => unreachable!(),
+        };
+
+        Ok(())
+    }
+
+    fn emit(&mut self, op: OpCode) -> usize {
+        let span = self.span;
+        self.state_mut().function.chunk.write(op, span)
+    }
+
+    fn code_len(&self) -> usize {
+        self.state().function.chunk.code().len()
+    }
+
+    fn state(&self) -> &FunctionState {
+        self.states.last().unwrap()
+    }
+
+    fn state_mut(&mut self) -> &mut FunctionState {
+        self.states.last_mut().unwrap()
+    }
+}
+
+impl Compiler {
+    fn construct_error(&self, kind: CompilerErrorKind) -> InterpreterError<CompilerError> {
+        InterpreterError::new(CompilerError::new(kind, self.span))
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+use error::{span::Span, InterpreterError};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CompilerErrorKind {
+    #[error("Too many constants in one chunk.")]
+    TooManyConstants,
+
+    #[error("Too many local variables in function.")]
+    TooManyLocals,
+
+    #[error("Too many closure variables in function.")]
+    TooManyUpvalues,
+
+    #[error("Too many arguments.")]
+    TooManyArguments,
+
+    #[error("Too many elements in a literal.")]
+    TooManyElements,
+
+    #[error("Too much code to jump over.")]
+    JumpTooLarge,
+}
+
+#[derive(Debug)]
+pub struct CompilerError {
+    kind: CompilerErrorKind,
+    span: Span,
+}
+
+impl error::Error for CompilerError {
+    fn span(&self) -> Option<Span> {
+        Some(self.span)
+    }
+
+    fn kind(&self) -> error::ErrorKind {
+        error::ErrorKind::Comptime
+    }
+}
+
+impl std::error::Error for CompilerError {}
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl CompilerError {
+    pub fn new(kind: CompilerErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+pub type CompilerResult<T> = Result<T, InterpreterError<CompilerError>>;
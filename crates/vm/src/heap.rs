@@ -0,0 +1,369 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    mem,
+    rc::{Rc, Weak},
+};
+
+use runtime::runtime::{heap::GcStats, map::Entries};
+
+use crate::value::{
+    BoundListMethod, BoundMapMethod, BoundMethod, Class, Closure, Instance, Upvalue, Value,
+};
+
+/// Objects tracked before the first collection.
+const INITIAL_COLLECTION_THRESHOLD: usize = 1024;
+
+/// Registry of the objects allocated by a VM that can take part in reference cycles.
+///
+/// Objects are still freed by reference counting. The heap only breaks the cycles
+/// counting alone can never free, e.g. a list holding itself or a local function
+/// capturing its own variable. Every such cycle passes through a list, map, instance,
+/// class or upvalue, which are the objects cleared to break it.
+#[derive(Debug, Default)]
+pub struct Heap {
+    objects: RefCell<Vec<Tracked>>,
+    /// Number of tracked objects that survived the last collection.
+    survivors: Cell<usize>,
+    collections: Cell<u64>,
+    collected: Cell<u64>,
+}
+
+#[derive(Debug)]
+enum Tracked {
+    List(Weak<RefCell<Vec<Value>>>),
+    Map(Weak<RefCell<Entries<Value>>>),
+    Instance(Weak<Instance>),
+    Class(Weak<Class>),
+    Closure(Weak<Closure>),
+    BoundMethod(Weak<BoundMethod>),
+    ListMethod(Weak<BoundListMethod>),
+    MapMethod(Weak<BoundMapMethod>),
+    Upvalue(Weak<RefCell<Upvalue>>),
+}
+
+/// A tracked object kept alive for the duration of a collection.
+enum Object {
+    Value(Value),
+    Upvalue(Rc<RefCell<Upvalue>>),
+}
+
+impl Tracked {
+    fn upgrade(&self) -> Option<Object> {
+        Some(match self {
+            Tracked::List(list) => Object::Value(Value::List(list.upgrade()?)),
+            Tracked::Map(map) => Object::Value(Value::Map(map.upgrade()?)),
+            Tracked::Instance(instance) => Object::Value(Value::Instance(instance.upgrade()?)),
+            Tracked::Class(class) => Object::Value(Value::Class(class.upgrade()?)),
+            Tracked::Closure(closure) => Object::Value(Value::Closure(closure.upgrade()?)),
+            Tracked::BoundMethod(bound) => Object::Value(Value::BoundMethod(bound.upgrade()?)),
+            Tracked::ListMethod(bound) => Object::Value(Value::ListMethod(bound.upgrade()?)),
+            Tracked::MapMethod(bound) => Object::Value(Value::MapMethod(bound.upgrade()?)),
+            Tracked::Upvalue(upvalue) => Object::Upvalue(upvalue.upgrade()?),
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        match self {
+            Tracked::List(list) => list.strong_count() > 0,
+            Tracked::Map(map) => map.strong_count() > 0,
+            Tracked::Instance(instance) => instance.strong_count() > 0,
+            Tracked::Class(class) => class.strong_count() > 0,
+            Tracked::Closure(closure) => closure.strong_count() > 0,
+            Tracked::BoundMethod(bound) => bound.strong_count() > 0,
+            Tracked::ListMethod(bound) => bound.strong_count() > 0,
+            Tracked::MapMethod(bound) => bound.strong_count() > 0,
+            Tracked::Upvalue(upvalue) => upvalue.strong_count() > 0,
+        }
+    }
+}
+
+/// Address of the object `value` refers to, for values that can be tracked.
+fn address(value: &Value) -> Option<*const ()> {
+    Some(match value {
+        Value::List(list) => Rc::as_ptr(list) as *const (),
+        Value::Map(map) => Rc::as_ptr(map) as *const (),
+        Value::Instance(instance) => Rc::as_ptr(instance) as *const (),
+        Value::Class(class) => Rc::as_ptr(class) as *const (),
+        Value::Closure(closure) => Rc::as_ptr(closure) as *const (),
+        Value::BoundMethod(bound) => Rc::as_ptr(bound) as *const (),
+        Value::ListMethod(bound) => Rc::as_ptr(bound) as *const (),
+        Value::MapMethod(bound) => Rc::as_ptr(bound) as *const (),
+        _ => return None,
+    })
+}
+
+fn visit_value(value: &Value, visit: &mut dyn FnMut(*const ())) {
+    if let Some(address) = address(value) {
+        visit(address);
+    }
+}
+
+impl Object {
+    fn address(&self) -> *const () {
+        match self {
+            Object::Value(value) => address(value).unwrap(),
+            Object::Upvalue(upvalue) => Rc::as_ptr(upvalue) as *const (),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Object::Value(Value::List(list)) => Rc::strong_count(list),
+            Object::Value(Value::Map(map)) => Rc::strong_count(map),
+            Object::Value(Value::Instance(instance)) => Rc::strong_count(instance),
+            Object::Value(Value::Class(class)) => Rc::strong_count(class),
+            Object::Value(Value::Closure(closure)) => Rc::strong_count(closure),
+            Object::Value(Value::BoundMethod(bound)) => Rc::strong_count(bound),
+            Object::Value(Value::ListMethod(bound)) => Rc::strong_count(bound),
+            Object::Value(Value::MapMethod(bound)) => Rc::strong_count(bound),
+            Object::Value(_) => unreachable!("only objects that can be tracked are upgraded"),
+            Object::Upvalue(upvalue) => Rc::strong_count(upvalue),
+        }
+    }
+
+    /// Visits the address of every object this one holds a strong reference to.
+    /// Returns `false` if the object is being mutated and could not be inspected.
+    fn trace(&self, visit: &mut dyn FnMut(*const ())) -> bool {
+        match self {
+            Object::Upvalue(upvalue) => match upvalue.try_borrow() {
+                Ok(upvalue) => {
+                    if let Upvalue::Closed(closed) = &*upvalue {
+                        visit_value(closed, visit);
+                    }
+                    true
+                }
+                Err(_) => false,
+            },
+            Object::Value(Value::List(list)) => match list.try_borrow() {
+                Ok(values) => {
+                    values.iter().for_each(|value| visit_value(value, visit));
+                    true
+                }
+                Err(_) => false,
+            },
+            Object::Value(Value::Map(map)) => match map.try_borrow() {
+                Ok(entries) => {
+                    entries.values().for_each(|(key, entry)| {
+                        visit_value(key, visit);
+                        visit_value(entry, visit);
+                    });
+                    true
+                }
+                Err(_) => false,
+            },
+            Object::Value(Value::Instance(instance)) => {
+                visit(Rc::as_ptr(&instance.class) as *const ());
+
+                match instance.fields.try_borrow() {
+                    Ok(fields) => {
+                        fields.values().for_each(|value| visit_value(value, visit));
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }
+            Object::Value(Value::Class(class)) => match class.methods.try_borrow() {
+                Ok(methods) => {
+                    methods
+                        .values()
+                        .for_each(|method| visit(Rc::as_ptr(method) as *const ()));
+                    true
+                }
+                Err(_) => false,
+            },
+            Object::Value(Value::Closure(closure)) => {
+                closure
+                    .upvalues
+                    .iter()
+                    .for_each(|upvalue| visit(Rc::as_ptr(upvalue) as *const ()));
+                true
+            }
+            Object::Value(Value::BoundMethod(bound)) => {
+                visit_value(&bound.receiver, visit);
+                visit(Rc::as_ptr(&bound.method) as *const ());
+                true
+            }
+            Object::Value(Value::ListMethod(bound)) => {
+                visit(Rc::as_ptr(&bound.list) as *const ());
+                true
+            }
+            Object::Value(Value::MapMethod(bound)) => {
+                visit(Rc::as_ptr(&bound.map) as *const ());
+                true
+            }
+            Object::Value(_) => true,
+        }
+    }
+
+    /// Drops the references that can form cycles, handing them back so they are
+    /// released only once every garbage object has been cleared.
+    fn clear(&self) -> Vec<Value> {
+        match self {
+            Object::Upvalue(upvalue) => match upvalue.try_borrow_mut().as_deref_mut() {
+                Ok(Upvalue::Closed(value)) => vec![mem::replace(value, Value::Nil)],
+                _ => vec![],
+            },
+            Object::Value(Value::List(list)) => list
+                .try_borrow_mut()
+                .map(|mut values| mem::take(&mut *values))
+                .unwrap_or_default(),
+            Object::Value(Value::Map(map)) => map
+                .try_borrow_mut()
+                .map(|mut entries| {
+                    mem::take(&mut *entries)
+                        .into_values()
+                        .flat_map(|(key, value)| [key, value])
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Object::Value(Value::Instance(instance)) => instance
+                .fields
+                .try_borrow_mut()
+                .map(|mut fields| mem::take(&mut *fields).into_values().collect())
+                .unwrap_or_default(),
+            Object::Value(Value::Class(class)) => class
+                .methods
+                .try_borrow_mut()
+                .map(|mut methods| {
+                    mem::take(&mut *methods)
+                        .into_values()
+                        .map(Value::Closure)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Object::Value(_) => vec![],
+        }
+    }
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collection statistics. The VM does not estimate the size of its objects, so
+    /// `bytes` is always zero.
+    pub fn stats(&self) -> GcStats {
+        GcStats {
+            collections: self.collections.get(),
+            collected: self.collected.get(),
+            objects: self.objects.borrow().len(),
+            bytes: 0,
+        }
+    }
+
+    /// Starts tracking `value` if it can take part in a cycle.
+    pub fn track_value(&self, value: &Value) {
+        let tracked = match value {
+            Value::List(list) => Tracked::List(Rc::downgrade(list)),
+            Value::Map(map) => Tracked::Map(Rc::downgrade(map)),
+            Value::Instance(instance) => Tracked::Instance(Rc::downgrade(instance)),
+            Value::Class(class) => Tracked::Class(Rc::downgrade(class)),
+            Value::Closure(closure) => Tracked::Closure(Rc::downgrade(closure)),
+            Value::BoundMethod(bound) => Tracked::BoundMethod(Rc::downgrade(bound)),
+            Value::ListMethod(bound) => Tracked::ListMethod(Rc::downgrade(bound)),
+            Value::MapMethod(bound) => Tracked::MapMethod(Rc::downgrade(bound)),
+            _ => return,
+        };
+
+        self.track(tracked);
+    }
+
+    pub fn track_upvalue(&self, upvalue: &Rc<RefCell<Upvalue>>) {
+        self.track(Tracked::Upvalue(Rc::downgrade(upvalue)));
+    }
+
+    /// Frees tracked objects that are only reachable through reference cycles
+    /// and returns how many there were.
+    ///
+    /// Roots are found by subtracting the references objects hold to each other
+    /// from their reference counts: whatever is left comes from outside the heap,
+    /// such as the VM's stack, its globals or values held by the host.
+    pub fn collect(&self) -> usize {
+        let mut indices = HashMap::new();
+        let mut objects = vec![];
+
+        // values handed back by natives may be tracked more than once
+        for object in self.objects.borrow().iter().filter_map(Tracked::upgrade) {
+            indices.entry(object.address()).or_insert_with(|| {
+                objects.push(object);
+                objects.len() - 1
+            });
+        }
+
+        let mut internal = vec![0; objects.len()];
+        let mut reachable = vec![false; objects.len()];
+
+        for (index, object) in objects.iter().enumerate() {
+            let traced = object.trace(&mut |address| {
+                if let Some(&child) = indices.get(&address) {
+                    internal[child] += 1;
+                }
+            });
+
+            // an object that cannot be inspected right now is kept, along with everything it holds
+            if !traced {
+                reachable[index] = true;
+            }
+        }
+
+        // one of the strong references is the upgrade held by `objects`
+        let mut pending = (0..objects.len())
+            .filter(|&index| {
+                reachable[index] || objects[index].strong_count() - 1 > internal[index]
+            })
+            .collect::<Vec<_>>();
+        pending.iter().for_each(|&index| reachable[index] = true);
+
+        while let Some(index) = pending.pop() {
+            objects[index].trace(&mut |address| {
+                if let Some(&child) = indices.get(&address) {
+                    if !reachable[child] {
+                        reachable[child] = true;
+                        pending.push(child);
+                    }
+                }
+            });
+        }
+
+        let garbage = objects
+            .iter()
+            .zip(&reachable)
+            .filter(|(_, reachable)| !**reachable)
+            .map(|(object, _)| object)
+            .collect::<Vec<_>>();
+        let collected = garbage.len();
+
+        let released = garbage
+            .into_iter()
+            .flat_map(Object::clear)
+            .collect::<Vec<_>>();
+
+        drop(released);
+        drop(objects);
+
+        self.collections.set(self.collections.get() + 1);
+        self.collected.set(self.collected.get() + collected as u64);
+        self.sweep();
+
+        collected
+    }
+
+    /// Forgets every object that has been dropped.
+    fn sweep(&self) {
+        self.objects.borrow_mut().retain(Tracked::is_alive);
+        self.survivors.set(self.objects.borrow().len());
+    }
+
+    fn track(&self, tracked: Tracked) {
+        self.objects.borrow_mut().push(tracked);
+
+        // collecting once the registry doubles keeps its size proportional to the live objects
+        if self.objects.borrow().len()
+            >= (2 * self.survivors.get()).max(INITIAL_COLLECTION_THRESHOLD)
+        {
+            self.collect();
+        }
+    }
+}
@@ -0,0 +1,1089 @@
+pub mod chunk;
+pub mod compiler;
+pub mod error;
+pub mod heap;
+pub mod value;
+
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+
+use ::error::InterpreterError;
+use chunk::OpCode;
+use compiler::Compiler;
+use heap::Heap;
+use runtime::{
+    error::{RuntimeError, RuntimeErrorKind, RuntimeResult},
+    runtime::{
+        heap::GcStats,
+        list::{self, ListMethod},
+        map::{self, Entries, MapKey, MapMethod},
+        module::{self, ModuleLoader},
+        native::{self, Library, NativeFunction},
+        string::{BoundStringMethod, StringMethod},
+        value::RuntimeValue,
+    },
+    Runtime,
+};
+use value::{
+    BoundListMethod, BoundMapMethod, BoundMethod, Class, Closure, Function, Globals, Instance,
+    Module, Upvalue, Value,
+};
+
+#[derive(Debug)]
+struct CallFrame {
+    closure: Rc<Closure>,
+    ip: usize,
+    /// Stack index of the frame's slot zero.
+    base: usize,
+    /// Set for frames running `init` on behalf of a class call, which
+    /// evaluate to the new instance instead of the initializer's result.
+    initializer: bool,
+    /// Set for frames running the top level of the module imported from the path,
+    /// which evaluate to the module.
+    module: Option<(PathBuf, Rc<Module>)>,
+}
+
+/// A `try` statement being executed.
+#[derive(Debug)]
+struct Handler {
+    /// Number of frames when the statement was entered.
+    frames: usize,
+    /// Stack height to restore before pushing the caught value.
+    stack: usize,
+    /// Start of the code handling the exception, in the frame that entered the statement.
+    ip: usize,
+}
+
+/// Stack-based virtual machine executing compiled [`Function`]s.
+#[derive(Debug)]
+pub struct Vm {
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+    /// Globals of the main program.
+    globals: Globals,
+    /// Natives and libraries defined by the host, also made available to every module.
+    natives: HashMap<String, Value>,
+    /// Modules already executed, by canonical path.
+    modules: HashMap<PathBuf, Rc<Module>>,
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    handlers: Vec<Handler>,
+    /// Errors to report for the exceptions handed to handlers, by the stack slot the
+    /// exception was pushed to, in case a `finally` block throws them on.
+    pending: Vec<(usize, InterpreterError<RuntimeError>)>,
+    /// Class of the values runtime errors are caught as.
+    error_class: Rc<Class>,
+    heap: Heap,
+    /// Context handed to native functions, which are shared with the tree-walker.
+    host: Runtime,
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Self::with_host(Runtime::new())
+    }
+
+    /// VM printing through, and handing natives, the given runtime.
+    pub fn with_host(host: Runtime) -> Self {
+        let mut vm = Self {
+            stack: vec![],
+            frames: vec![],
+            globals: Rc::new(RefCell::new(HashMap::new())),
+            natives: HashMap::new(),
+            modules: HashMap::new(),
+            open_upvalues: vec![],
+            handlers: vec![],
+            pending: vec![],
+            error_class: Rc::new(Class {
+                name: "Error".to_string(),
+                methods: RefCell::new(HashMap::new()),
+            }),
+            heap: Heap::new(),
+            host,
+        };
+
+        for builtin in native::core() {
+            vm.define_native(builtin);
+        }
+
+        for library in native::libraries() {
+            vm.define_library(library);
+        }
+
+        vm
+    }
+
+    /// Makes `function` available to Lox code under its own name. Programs may
+    /// still declare a global with that name, which then shadows the native.
+    pub fn define_native(&mut self, function: impl NativeFunction + 'static) {
+        let identifier = function.name().to_string();
+        let function = Value::Native(Rc::new(function));
+
+        self.natives.insert(identifier, function);
+    }
+
+    /// Makes the members of `library` available to Lox code through a name of its
+    /// own, which globals shadow like the names of natives.
+    pub fn define_library(&mut self, library: Library) {
+        let identifier = library.name().to_string();
+
+        let members = library
+            .into_members()
+            .into_iter()
+            .map(|(name, value)| {
+                let value = Value::from_runtime(&value).expect("library members are plain data");
+                (name, value)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let module = Value::Module(Rc::new(Module {
+            name: identifier.clone(),
+            exports: members.keys().cloned().collect(),
+            globals: Rc::new(RefCell::new(members)),
+        }));
+
+        self.natives.insert(identifier, module);
+    }
+
+    /// Locates the files imported by the program.
+    pub fn modules(&self) -> &ModuleLoader {
+        self.host.modules()
+    }
+
+    /// Runs the cycle collector now, returning the number of objects it freed.
+    /// Collections also happen on their own as objects are allocated.
+    pub fn collect_garbage(&self) -> usize {
+        self.heap.collect()
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.heap.stats()
+    }
+
+    /// Runs a compiled script. Fails without running it if the host sets a memory
+    /// limit, as the VM does not account for the memory it uses.
+    pub fn run(&mut self, function: Rc<Function>) -> RuntimeResult<()> {
+        if self.host.limits().max_memory.is_some() {
+            return Err(InterpreterError::new(RuntimeError::new(
+                RuntimeErrorKind::UnsupportedLimit("max_memory"),
+            )));
+        }
+
+        let closure = Rc::new(Closure {
+            function,
+            upvalues: vec![],
+            globals: Rc::clone(&self.globals),
+        });
+
+        self.stack.push(Value::Closure(Rc::clone(&closure)));
+        self.frames.push(CallFrame {
+            closure,
+            ip: 0,
+            base: 0,
+            initializer: false,
+            module: None,
+        });
+
+        let result = self.execute();
+
+        if result.is_err() {
+            self.stack.clear();
+            self.frames.clear();
+            self.open_upvalues.clear();
+            self.handlers.clear();
+        }
+        self.pending.clear();
+
+        result
+    }
+
+    /// Runs until the script returns, handing catchable errors to the closest `try`.
+    fn execute(&mut self) -> RuntimeResult<()> {
+        loop {
+            match self.dispatch() {
+                Err(error) if error.source().is_catchable() && !self.handlers.is_empty() => {
+                    let value = self.error_value(error.source());
+                    self.unwind(value, error);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn dispatch(&mut self) -> RuntimeResult<()> {
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            let op = frame.closure.function.chunk.code()[frame.ip];
+            frame.ip += 1;
+
+            self.host.tick().map_err(|error| self.locate(error))?;
+
+            match op {
+                OpCode::Constant(index) => {
+                    let constant = self.constant(index);
+                    self.stack.push(constant);
+                }
+                OpCode::Nil => self.stack.push(Value::Nil),
+                OpCode::True => self.stack.push(Value::Boolean(true)),
+                OpCode::False => self.stack.push(Value::Boolean(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetLocal(slot) => {
+                    let value = self.stack[self.frame().base + slot as usize].clone();
+                    self.stack.push(value);
+                }
+                OpCode::SetLocal(slot) => {
+                    let index = self.frame().base + slot as usize;
+                    self.stack[index] = self.peek(0).clone();
+                }
+                OpCode::GetGlobal(index) => {
+                    let name = self.constant_string(index);
+                    let value = self
+                        .frame()
+                        .closure
+                        .globals
+                        .borrow()
+                        .get(name.as_ref())
+                        .or_else(|| self.natives.get(name.as_ref()))
+                        .cloned()
+                        .ok_or_else(|| {
+                            self.error(RuntimeErrorKind::VariableNotDefined(name.to_string()))
+                        })?;
+                    self.stack.push(value);
+                }
+                OpCode::DefineGlobal(index) => {
+                    let name = self.constant_string(index);
+                    let globals = Rc::clone(&self.frame().closure.globals);
+
+                    if globals.borrow().contains_key(name.as_ref()) {
+                        return Err(
+                            self.error(RuntimeErrorKind::VariableAlreadyDefined(name.to_string()))
+                        );
+                    }
+
+                    let value = self.pop();
+                    globals.borrow_mut().insert(name.to_string(), value);
+                }
+                OpCode::SetGlobal(index) => {
+                    let name = self.constant_string(index);
+                    let value = self.peek(0).clone();
+                    let globals = Rc::clone(&self.frame().closure.globals);
+
+                    // assigning to a native gives the module a global of that name
+                    if !globals.borrow().contains_key(name.as_ref())
+                        && !self.natives.contains_key(name.as_ref())
+                    {
+                        return Err(
+                            self.error(RuntimeErrorKind::VariableNotDefined(name.to_string()))
+                        );
+                    }
+
+                    globals.borrow_mut().insert(name.to_string(), value);
+                }
+                OpCode::GetUpvalue(index) => {
+                    let upvalue = Rc::clone(&self.frame().closure.upvalues[index as usize]);
+                    let value = match &*upvalue.borrow() {
+                        Upvalue::Open(slot) => self.stack[*slot].clone(),
+                        Upvalue::Closed(value) => value.clone(),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::SetUpvalue(index) => {
+                    let upvalue = Rc::clone(&self.frame().closure.upvalues[index as usize]);
+                    let value = self.peek(0).clone();
+                    match &mut *upvalue.borrow_mut() {
+                        Upvalue::Open(slot) => self.stack[*slot] = value,
+                        Upvalue::Closed(closed) => *closed = value,
+                    };
+                }
+                OpCode::GetProperty(index) => {
+                    let name = self.constant_string(index);
+
+                    if let Value::List(list) = self.peek(0).clone() {
+                        let method = ListMethod::from_name(&name).ok_or_else(|| {
+                            self.error(RuntimeErrorKind::UndefinedProperty(name.to_string()))
+                        })?;
+
+                        let method = Value::ListMethod(Rc::new(BoundListMethod { list, method }));
+                        self.pop();
+                        self.push_object(method);
+                        continue;
+                    }
+
+                    if let Value::String(string) = self.peek(0).clone() {
+                        let method = StringMethod::from_name(&name).ok_or_else(|| {
+                            self.error(RuntimeErrorKind::UndefinedProperty(name.to_string()))
+                        })?;
+
+                        // string methods are natives shared with the tree-walker
+                        let string = Rc::new(RuntimeValue::string(string.to_string()));
+                        self.pop();
+                        self.stack
+                            .push(Value::Native(Rc::new(BoundStringMethod::new(
+                                string, method,
+                            ))));
+                        continue;
+                    }
+
+                    if let Value::Module(module) = self.peek(0).clone() {
+                        let value = module
+                            .exports
+                            .iter()
+                            .any(|export| **export == *name)
+                            .then(|| module.globals.borrow().get(name.as_ref()).cloned())
+                            .flatten()
+                            .ok_or_else(|| {
+                                self.locate(module::undefined_export(&module.name, &name))
+                            })?;
+
+                        self.pop();
+                        self.stack.push(value);
+                        continue;
+                    }
+
+                    if let Value::Map(map) = self.peek(0).clone() {
+                        let method = MapMethod::from_name(&name).ok_or_else(|| {
+                            self.error(RuntimeErrorKind::UndefinedProperty(name.to_string()))
+                        })?;
+
+                        let method = Value::MapMethod(Rc::new(BoundMapMethod { map, method }));
+                        self.pop();
+                        self.push_object(method);
+                        continue;
+                    }
+
+                    let Value::Instance(instance) = self.peek(0).clone() else {
+                        return Err(self.error(RuntimeErrorKind::ExpressionNotInstance));
+                    };
+
+                    let field = instance.fields.borrow().get(name.as_ref()).cloned();
+                    let value = match field {
+                        Some(value) => value,
+                        None => self.bind_method(&instance.class, &name)?,
+                    };
+
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::SetProperty(index) => {
+                    let name = self.constant_string(index);
+
+                    let Value::Instance(instance) = self.peek(1).clone() else {
+                        return Err(self.error(RuntimeErrorKind::ExpressionNotInstance));
+                    };
+
+                    let value = self.pop();
+                    instance.fields.borrow_mut().insert(name.to_string(), value);
+
+                    self.pop();
+                    self.stack.push(Value::Nil);
+                }
+                OpCode::GetSuper(index) => {
+                    let name = self.constant_string(index);
+
+                    let Value::Class(superclass) = self.pop() else {
+                        unreachable!()
+                    };
+
+                    let method = self.bind_method(&superclass, &name)?;
+                    self.pop();
+                    self.stack.push(method);
+                }
+                OpCode::Equal => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(Value::Boolean(left == right));
+                }
+                OpCode::Greater => self.comparison(|left, right| left > right),
+                OpCode::GreaterEqual => self.comparison(|left, right| left >= right),
+                OpCode::Less => self.comparison(|left, right| left < right),
+                OpCode::LessEqual => self.comparison(|left, right| left <= right),
+                OpCode::Add => self.arithmetic(Value::add)?,
+                OpCode::Subtract => self.arithmetic(Value::subtract)?,
+                OpCode::Multiply => self.arithmetic(Value::multiply)?,
+                OpCode::Divide => {
+                    if self.peek(0).is_zero() {
+                        return Err(self.error(RuntimeErrorKind::ZeroDivision));
+                    }
+
+                    self.arithmetic(Value::divide)?
+                }
+                OpCode::Not => {
+                    let value = self.pop();
+                    self.stack.push(Value::Boolean(!value.is_truthy()));
+                }
+                OpCode::Negate => {
+                    let value = self
+                        .peek(0)
+                        .negate()
+                        .ok_or_else(|| self.error(RuntimeErrorKind::ExpectedNumberOperand))?;
+                    self.pop();
+                    self.stack.push(value);
+                }
+                OpCode::Print => {
+                    let value = self.pop();
+                    self.host
+                        .write_line(value)
+                        .map_err(|error| self.locate(error))?;
+                }
+                OpCode::Jump(offset) => self.frame_mut().ip += offset as usize,
+                OpCode::JumpIfFalse(offset) => {
+                    if !self.peek(0).is_truthy() {
+                        self.frame_mut().ip += offset as usize;
+                    }
+                }
+                OpCode::Loop(offset) => self.frame_mut().ip -= offset as usize,
+                OpCode::Call(count) => {
+                    let callee = self.peek(count as usize).clone();
+                    self.call_value(callee, count as usize)?;
+                }
+                OpCode::Closure(index) => {
+                    let Value::Function(function) = self.constant(index) else {
+                        unreachable!()
+                    };
+
+                    let upvalues = function
+                        .upvalues
+                        .iter()
+                        .map(|upvalue| {
+                            if upvalue.is_local {
+                                self.capture_upvalue(self.frame().base + upvalue.index as usize)
+                            } else {
+                                Rc::clone(&self.frame().closure.upvalues[upvalue.index as usize])
+                            }
+                        })
+                        .collect();
+
+                    let globals = Rc::clone(&self.frame().closure.globals);
+                    self.push_object(Value::Closure(Rc::new(Closure {
+                        function,
+                        upvalues,
+                        globals,
+                    })));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::PushHandler(offset) => {
+                    let ip = self.frame().ip + offset as usize;
+                    self.handlers.push(Handler {
+                        frames: self.frames.len(),
+                        stack: self.stack.len(),
+                        ip,
+                    });
+                }
+                OpCode::PopHandler => {
+                    self.handlers.pop();
+                }
+                OpCode::Throw => {
+                    let value = self.pop();
+                    let description = self.describe(&value);
+                    let error = self.error(RuntimeErrorKind::UncaughtException(description));
+
+                    if self.handlers.is_empty() {
+                        return Err(error);
+                    }
+
+                    self.unwind(value, error);
+                }
+                OpCode::Rethrow(slot) => {
+                    let slot = self.frame().base + slot as usize;
+                    let value = self.stack[slot].clone();
+
+                    let error = match self.pending.iter().rposition(|&(at, _)| at == slot) {
+                        Some(index) => self.pending.remove(index).1,
+                        None => {
+                            let description = self.describe(&value);
+                            self.error(RuntimeErrorKind::UncaughtException(description))
+                        }
+                    };
+
+                    if self.handlers.is_empty() {
+                        return Err(error);
+                    }
+
+                    self.unwind(value, error);
+                }
+                OpCode::Import(index) => {
+                    let specifier = self.constant_string(index);
+                    self.import(&specifier)?;
+                }
+                OpCode::Return => {
+                    let mut result = self.pop();
+                    let frame = self.frames.pop().unwrap();
+
+                    if frame.initializer {
+                        result = self.stack[frame.base].clone();
+                    }
+
+                    if let Some((path, module)) = frame.module {
+                        self.modules.insert(path, Rc::clone(&module));
+                        result = Value::Module(module);
+                    }
+
+                    self.close_upvalues(frame.base);
+                    self.stack.truncate(frame.base);
+
+                    if self.frames.is_empty() {
+                        return Ok(());
+                    }
+
+                    self.stack.push(result);
+                }
+                OpCode::Class(index) => {
+                    let name = self.constant_string(index);
+                    self.push_object(Value::Class(Rc::new(Class {
+                        name: name.to_string(),
+                        methods: RefCell::new(HashMap::new()),
+                    })));
+                }
+                OpCode::Inherit => {
+                    let Value::Class(superclass) = self.peek(1).clone() else {
+                        return Err(self.error(RuntimeErrorKind::SuperclassNotClass));
+                    };
+                    let Value::Class(subclass) = self.pop() else {
+                        unreachable!()
+                    };
+
+                    subclass
+                        .methods
+                        .borrow_mut()
+                        .extend(superclass.methods.borrow().clone());
+                }
+                OpCode::Method(index) => {
+                    let name = self.constant_string(index);
+
+                    let Value::Closure(method) = self.pop() else {
+                        unreachable!()
+                    };
+                    let Value::Class(class) = self.peek(0) else {
+                        unreachable!()
+                    };
+
+                    class.methods.borrow_mut().insert(name.to_string(), method);
+                }
+                OpCode::BuildList(count) => {
+                    let values = self.stack.split_off(self.stack.len() - count as usize);
+                    self.push_object(Value::List(Rc::new(RefCell::new(values))));
+                }
+                OpCode::Interpolate(count) => {
+                    let parts = self.stack.split_off(self.stack.len() - count as usize);
+                    let value = parts.iter().map(Value::to_string).collect::<String>();
+
+                    self.stack.push(Value::String(value.into()));
+                }
+                OpCode::BuildMap(count) => {
+                    let values = self.stack.split_off(self.stack.len() - 2 * count as usize);
+
+                    let mut entries = Entries::new();
+                    for pair in values.chunks_exact(2) {
+                        let key = self.map_key(&pair[0])?;
+                        map::insert(&mut entries, key, pair[0].clone(), pair[1].clone());
+                    }
+
+                    self.push_object(Value::Map(Rc::new(RefCell::new(entries))));
+                }
+                OpCode::GetIndex => {
+                    let index = self.pop();
+
+                    let value = match self.pop() {
+                        Value::List(values) => {
+                            let index = self.integer_index(&index)?;
+                            let values = values.borrow();
+
+                            list::position(index, values.len())
+                                .map(|position| values[position].clone())
+                                .ok_or_else(|| {
+                                    self.locate(list::out_of_bounds(index, values.len()))
+                                })?
+                        }
+                        Value::Map(entries) => {
+                            let key = self.map_key(&index)?;
+                            let value = entries.borrow().get(&key).map(|(_, value)| value.clone());
+
+                            value.ok_or_else(|| self.locate(map::missing_key(&key)))?
+                        }
+                        _ => return Err(self.error(RuntimeErrorKind::ExpressionNotIndexable)),
+                    };
+
+                    self.stack.push(value);
+                }
+                OpCode::SetIndex => {
+                    let value = self.pop();
+                    let index = self.pop();
+
+                    match self.pop() {
+                        Value::List(values) => {
+                            let index = self.integer_index(&index)?;
+                            let len = values.borrow().len();
+                            let position = list::position(index, len)
+                                .ok_or_else(|| self.locate(list::out_of_bounds(index, len)))?;
+
+                            values.borrow_mut()[position] = value;
+                        }
+                        Value::Map(entries) => {
+                            let key = self.map_key(&index)?;
+                            map::insert(&mut entries.borrow_mut(), key, index, value);
+                        }
+                        _ => return Err(self.error(RuntimeErrorKind::ExpressionNotIndexable)),
+                    }
+
+                    self.stack.push(Value::Nil);
+                }
+                OpCode::Slice => {
+                    let end = self.pop();
+                    let start = self.pop();
+
+                    // nil stands for a bound that was left out
+                    let start = match start {
+                        Value::Nil => None,
+                        start => Some(self.integer_index(&start)?),
+                    };
+                    let end = match end {
+                        Value::Nil => None,
+                        end => Some(self.integer_index(&end)?),
+                    };
+
+                    let Value::List(values) = self.pop() else {
+                        return Err(self.error(RuntimeErrorKind::ExpressionNotSliceable));
+                    };
+
+                    let selected = {
+                        let values = values.borrow();
+                        values[list::slice(start, end, values.len())].to_vec()
+                    };
+                    self.push_object(Value::List(Rc::new(RefCell::new(selected))));
+                }
+            }
+        }
+    }
+
+    fn call_value(&mut self, callee: Value, count: usize) -> RuntimeResult<()> {
+        match callee {
+            Value::Closure(closure) => self.call(closure, count, false),
+            Value::BoundMethod(bound) => {
+                let receiver = self.stack.len() - count - 1;
+                self.stack[receiver] = bound.receiver.clone();
+                self.call(Rc::clone(&bound.method), count, false)
+            }
+            Value::Class(class) => {
+                let receiver = self.stack.len() - count - 1;
+                let instance = Value::Instance(Rc::new(Instance {
+                    class: Rc::clone(&class),
+                    fields: RefCell::new(HashMap::new()),
+                }));
+                self.heap.track_value(&instance);
+                self.stack[receiver] = instance;
+
+                let initializer = class.methods.borrow().get("init").cloned();
+                match initializer {
+                    Some(initializer) => self.call(initializer, count, true),
+                    None if count != 0 => {
+                        Err(self.error(RuntimeErrorKind::InvalidArgumentCount(count, 0)))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Value::Native(function) => self.call_native(function.as_ref(), count),
+            Value::ListMethod(bound) => self.call_list_method(&bound, count),
+            Value::MapMethod(bound) => self.call_map_method(&bound, count),
+            _ => Err(self.error(RuntimeErrorKind::ExpressionNotCallable)),
+        }
+    }
+
+    fn call_native(&mut self, function: &dyn NativeFunction, count: usize) -> RuntimeResult<()> {
+        if count != function.arity() {
+            return Err(self.error(RuntimeErrorKind::InvalidArgumentCount(
+                count,
+                function.arity(),
+            )));
+        }
+
+        let arguments = self.stack[self.stack.len() - count..]
+            .iter()
+            .map(|argument| Rc::new(argument.to_runtime()))
+            .collect::<Vec<_>>();
+
+        let result = function
+            .call(&self.host, &arguments)
+            .map_err(|error| self.locate(error))?;
+        let result = Value::from_runtime(&result)
+            .ok_or_else(|| self.error(RuntimeErrorKind::UnsupportedNativeValue))?;
+
+        // pop the arguments and the callee
+        self.stack.truncate(self.stack.len() - count - 1);
+        self.push_object(result);
+
+        Ok(())
+    }
+
+    fn call_list_method(&mut self, bound: &BoundListMethod, count: usize) -> RuntimeResult<()> {
+        if count != bound.method.arity() {
+            return Err(self.error(RuntimeErrorKind::InvalidArgumentCount(
+                count,
+                bound.method.arity(),
+            )));
+        }
+
+        let arguments = self.stack.split_off(self.stack.len() - count);
+        let mut values = bound.list.borrow_mut();
+
+        let result = match bound.method {
+            ListMethod::Push => {
+                values.extend(arguments);
+                Value::Nil
+            }
+            ListMethod::Pop => values
+                .pop()
+                .ok_or_else(|| self.locate(list::out_of_bounds(-1, 0)))?,
+            ListMethod::Insert => {
+                let index = self.integer_index(&arguments[0])?;
+                let position = list::insertion_position(index, values.len())
+                    .ok_or_else(|| self.locate(list::out_of_bounds(index, values.len())))?;
+
+                values.insert(position, arguments[1].clone());
+                Value::Nil
+            }
+            ListMethod::Remove => {
+                let index = self.integer_index(&arguments[0])?;
+                let position = list::position(index, values.len())
+                    .ok_or_else(|| self.locate(list::out_of_bounds(index, values.len())))?;
+
+                values.remove(position)
+            }
+            ListMethod::Contains => Value::Boolean(values.contains(&arguments[0])),
+            ListMethod::Join => {
+                let Value::String(separator) = &arguments[0] else {
+                    return Err(self.locate(native::invalid_argument("join", "a string")));
+                };
+
+                let joined = values
+                    .iter()
+                    .map(Value::to_string)
+                    .collect::<Vec<_>>()
+                    .join(separator);
+
+                Value::String(joined.into())
+            }
+        };
+
+        drop(values);
+
+        // pop the callee
+        self.pop();
+        self.stack.push(result);
+
+        Ok(())
+    }
+
+    fn call_map_method(&mut self, bound: &BoundMapMethod, count: usize) -> RuntimeResult<()> {
+        if count != bound.method.arity() {
+            return Err(self.error(RuntimeErrorKind::InvalidArgumentCount(
+                count,
+                bound.method.arity(),
+            )));
+        }
+
+        let arguments = self.stack.split_off(self.stack.len() - count);
+        let mut entries = bound.map.borrow_mut();
+
+        let result = match bound.method {
+            MapMethod::Keys => Value::List(Rc::new(RefCell::new(
+                entries.values().map(|(key, _)| key.clone()).collect(),
+            ))),
+            MapMethod::Values => Value::List(Rc::new(RefCell::new(
+                entries.values().map(|(_, value)| value.clone()).collect(),
+            ))),
+            MapMethod::Has => Value::Boolean(entries.contains_key(&self.map_key(&arguments[0])?)),
+            MapMethod::Delete => {
+                let key = self.map_key(&arguments[0])?;
+
+                entries
+                    .shift_remove(&key)
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| self.locate(map::missing_key(&key)))?
+            }
+        };
+
+        drop(entries);
+
+        // pop the callee
+        self.pop();
+        self.push_object(result);
+
+        Ok(())
+    }
+
+    fn map_key(&self, key: &Value) -> RuntimeResult<MapKey> {
+        key.key()
+            .ok_or_else(|| self.error(RuntimeErrorKind::InvalidMapKey))
+    }
+
+    fn integer_index(&self, index: &Value) -> RuntimeResult<i64> {
+        match index {
+            Value::Integer(index) => Ok(*index),
+            _ => Err(self.error(RuntimeErrorKind::IndexNotInteger)),
+        }
+    }
+
+    fn call(&mut self, closure: Rc<Closure>, count: usize, initializer: bool) -> RuntimeResult<()> {
+        if count != closure.function.arity {
+            return Err(self.error(RuntimeErrorKind::InvalidArgumentCount(
+                count,
+                closure.function.arity,
+            )));
+        }
+
+        self.check_call_depth()?;
+
+        self.frames.push(CallFrame {
+            closure,
+            ip: 0,
+            base: self.stack.len() - count - 1,
+            initializer,
+            module: None,
+        });
+
+        Ok(())
+    }
+
+    fn check_call_depth(&self) -> RuntimeResult<()> {
+        // the script itself occupies the first frame
+        let max_call_depth = self.host.limits().max_call_depth;
+        if self.frames.len() > max_call_depth {
+            return Err(self.error(RuntimeErrorKind::StackOverflow(max_call_depth)));
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the module imported as `specifier` by the current function, or starts
+    /// running it if this is the first import.
+    fn import(&mut self, specifier: &str) -> RuntimeResult<()> {
+        let importer = self.frame().closure.function.file.clone();
+        let path = self
+            .host
+            .resolve_import(specifier, importer.as_ref().map(|file| file.path.as_path()))
+            .map_err(|error| self.locate(error))?;
+
+        if let Some(module) = self.modules.get(&path) {
+            self.stack.push(Value::Module(Rc::clone(module)));
+            return Ok(());
+        }
+
+        let loading = self
+            .frames
+            .iter()
+            .filter_map(|frame| frame.module.as_ref())
+            .map(|(path, _)| path.as_path())
+            .collect::<Vec<_>>();
+        self.host
+            .modules()
+            .check_cycle(&loading, &path)
+            .map_err(|error| self.locate(error))?;
+
+        let (file, program) = module::load(&path).map_err(|error| self.locate(error))?;
+        let function = Compiler::with_file(Rc::clone(&file))
+            .compile(&program)
+            .map_err(|error| self.locate(module::invalid(&file, &error)))?;
+
+        self.check_call_depth()?;
+
+        let module = Rc::new(Module {
+            name: module::name(&path),
+            exports: module::exports(&program),
+            globals: Rc::new(RefCell::new(HashMap::new())),
+        });
+        let closure = Rc::new(Closure {
+            function,
+            upvalues: vec![],
+            globals: Rc::clone(&module.globals),
+        });
+
+        self.stack.push(Value::Closure(Rc::clone(&closure)));
+        self.frames.push(CallFrame {
+            closure,
+            ip: 0,
+            base: self.stack.len() - 1,
+            initializer: false,
+            module: Some((path, module)),
+        });
+
+        Ok(())
+    }
+
+    fn bind_method(&self, class: &Class, name: &str) -> RuntimeResult<Value> {
+        let method = class
+            .methods
+            .borrow()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| self.error(RuntimeErrorKind::UndefinedProperty(name.to_string())))?;
+
+        let bound = Value::BoundMethod(Rc::new(BoundMethod {
+            receiver: self.peek(0).clone(),
+            method,
+        }));
+        self.heap.track_value(&bound);
+
+        Ok(bound)
+    }
+
+    fn capture_upvalue(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let existing = self
+            .open_upvalues
+            .iter()
+            .find(|upvalue| matches!(*upvalue.borrow(), Upvalue::Open(open) if open == slot));
+
+        if let Some(upvalue) = existing {
+            return Rc::clone(upvalue);
+        }
+
+        let upvalue = Rc::new(RefCell::new(Upvalue::Open(slot)));
+        self.heap.track_upvalue(&upvalue);
+        self.open_upvalues.push(Rc::clone(&upvalue));
+        upvalue
+    }
+
+    /// Moves every captured variable living at or above `from` off the stack.
+    fn close_upvalues(&mut self, from: usize) {
+        let stack = &self.stack;
+
+        self.open_upvalues.retain(|upvalue| {
+            let slot = match *upvalue.borrow() {
+                Upvalue::Open(slot) => slot,
+                Upvalue::Closed(_) => return false,
+            };
+
+            if slot < from {
+                return true;
+            }
+
+            *upvalue.borrow_mut() = Upvalue::Closed(stack[slot].clone());
+            false
+        });
+    }
+
+    /// Resumes execution at the closest handler with `value` as the caught exception,
+    /// dropping the frames and stack slots entered since. `error` is reported if a
+    /// `finally` block throws the value on past the last handler.
+    fn unwind(&mut self, value: Value, error: InterpreterError<RuntimeError>) {
+        let handler = self.handlers.pop().unwrap();
+
+        // errors of exceptions whose slots are being dropped can no longer be thrown on
+        self.pending.retain(|&(slot, _)| slot < handler.stack);
+        self.pending.push((handler.stack, error));
+
+        self.frames.truncate(handler.frames);
+        self.close_upvalues(handler.stack);
+        self.stack.truncate(handler.stack);
+        self.stack.push(value);
+        self.frame_mut().ip = handler.ip;
+    }
+
+    /// Instance of the `Error` class describing `error`, with its `message` and `kind`.
+    fn error_value(&self, error: &RuntimeError) -> Value {
+        let fields = HashMap::from([
+            (
+                "message".to_string(),
+                Value::String(error.to_string().into()),
+            ),
+            ("kind".to_string(), Value::String(error.name().into())),
+        ]);
+
+        let instance = Value::Instance(Rc::new(Instance {
+            class: Rc::clone(&self.error_class),
+            fields: RefCell::new(fields),
+        }));
+        self.heap.track_value(&instance);
+
+        instance
+    }
+
+    /// Text of an uncaught exception. Caught runtime errors keep reading as the
+    /// original message when thrown again.
+    fn describe(&self, value: &Value) -> String {
+        match value {
+            Value::Instance(instance) if Rc::ptr_eq(&instance.class, &self.error_class) => {
+                instance.fields.borrow()["message"].to_string()
+            }
+            value => value.to_string(),
+        }
+    }
+
+    fn arithmetic(&mut self, operation: fn(&Value, &Value) -> Option<Value>) -> RuntimeResult<()> {
+        let value = operation(self.peek(1), self.peek(0))
+            .ok_or_else(|| self.error(RuntimeErrorKind::ExpectedNumberOperand))?;
+
+        self.pop();
+        self.pop();
+        self.stack.push(value);
+
+        Ok(())
+    }
+
+    fn comparison(&mut self, operation: fn(&Value, &Value) -> bool) {
+        let right = self.pop();
+        let left = self.pop();
+        self.stack.push(Value::Boolean(operation(&left, &right)));
+    }
+
+    fn constant(&self, index: u16) -> Value {
+        self.frame()
+            .closure
+            .function
+            .chunk
+            .constant(index as usize)
+            .clone()
+    }
+
+    fn constant_string(&self, index: u16) -> Rc<str> {
+        match self.constant(index) {
+            Value::String(name) => name,
+            _ => unreachable!(),
+        }
+    }
+
+    fn peek(&self, distance: usize) -> &Value {
+        &self.stack[self.stack.len() - 1 - distance]
+    }
+
+    /// Pushes a newly created `value`, tracking it in case it ends up in a cycle.
+    fn push_object(&mut self, value: Value) {
+        self.heap.track_value(&value);
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap()
+    }
+
+    fn frame(&self) -> &CallFrame {
+        self.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut CallFrame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn error(&self, kind: RuntimeErrorKind) -> InterpreterError<RuntimeError> {
+        self.locate(InterpreterError::new(RuntimeError::new(kind)))
+    }
+
+    /// Points `error` at the instruction currently being executed.
+    fn locate(&self, mut error: InterpreterError<RuntimeError>) -> InterpreterError<RuntimeError> {
+        let frame = self.frame();
+        let function = &frame.closure.function;
+        error
+            .source_mut()
+            .locate_in(function.chunk.span(frame.ip - 1), function.file.as_ref());
+
+        error
+    }
+}
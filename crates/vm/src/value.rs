@@ -0,0 +1,375 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use runtime::runtime::{
+    list::{self, ListMethod},
+    map::{self, Entries, MapKey, MapMethod},
+    module::ModuleFile,
+    native::NativeFunction,
+    value::{ForeignValue, RuntimeValue},
+};
+
+use crate::chunk::Chunk;
+
+/// Values manipulated by the VM. Semantics mirror `runtime::runtime::value::RuntimeValue`
+/// so both backends behave the same.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    String(Rc<str>),
+    Nil,
+    Boolean(bool),
+    Function(Rc<Function>),
+    Closure(Rc<Closure>),
+    Class(Rc<Class>),
+    Instance(Rc<Instance>),
+    BoundMethod(Rc<BoundMethod>),
+    Native(Rc<dyn NativeFunction>),
+    List(Rc<RefCell<Vec<Value>>>),
+    ListMethod(Rc<BoundListMethod>),
+    Map(Rc<RefCell<Entries<Value>>>),
+    MapMethod(Rc<BoundMapMethod>),
+    Module(Rc<Module>),
+}
+
+/// Global variables of a module, shared by every closure created in it.
+pub type Globals = Rc<RefCell<HashMap<String, Value>>>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct UpvalueDescriptor {
+    pub index: u8,
+    pub is_local: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Function {
+    pub name: String,
+    pub arity: usize,
+    pub chunk: Chunk,
+    pub upvalues: Vec<UpvalueDescriptor>,
+    /// Module the function was written in, `None` for the main program.
+    pub file: Option<Rc<ModuleFile>>,
+}
+
+#[derive(Debug)]
+pub enum Upvalue {
+    /// Captured variable still living on the stack at the given index.
+    Open(usize),
+    Closed(Value),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub function: Rc<Function>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    pub globals: Globals,
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    pub methods: RefCell<HashMap<String, Rc<Closure>>>,
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: RefCell<HashMap<String, Value>>,
+}
+
+#[derive(Debug)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: Rc<Closure>,
+}
+
+/// An imported module, exposing the exported variables of its globals.
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub globals: Globals,
+    pub exports: Vec<String>,
+}
+
+/// A list method bound to its list, as produced by `xs.push`.
+#[derive(Debug)]
+pub struct BoundListMethod {
+    pub list: Rc<RefCell<Vec<Value>>>,
+    pub method: ListMethod,
+}
+
+/// A map method bound to its map, as produced by `m.keys`.
+#[derive(Debug)]
+pub struct BoundMapMethod {
+    pub map: Rc<RefCell<Entries<Value>>>,
+    pub method: MapMethod,
+}
+
+impl Value {
+    /// Integer when `value` is whole and in range, float otherwise.
+    pub fn number(value: f64) -> Self {
+        if value.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(&value) {
+            Self::Integer(value as i64)
+        } else {
+            Self::Float(value)
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Boolean(value) => *value,
+            Value::Float(value) => *value != 0.,
+            Value::Integer(value) => *value != 0,
+            Value::Nil => false,
+            _ => true,
+        }
+    }
+
+    pub fn negate(&self) -> Option<Value> {
+        match self {
+            Value::Integer(value) => Some(match value.checked_neg() {
+                Some(value) => Value::Integer(value),
+                None => Value::Float(-(*value as f64)),
+            }),
+            Value::Float(value) => Some(Value::Float(-value)),
+            _ => None,
+        }
+    }
+
+    pub fn add(&self, rhs: &Value) -> Option<Value> {
+        match (self, rhs) {
+            (Value::String(lhs), Value::String(rhs)) => {
+                Some(Value::String(format!("{lhs}{rhs}").into()))
+            }
+            (Value::String(lhs), Value::Integer(rhs)) => {
+                Some(Value::String(format!("{lhs}{rhs}").into()))
+            }
+            (Value::String(lhs), Value::Float(rhs)) => {
+                Some(Value::String(format!("{lhs}{rhs}").into()))
+            }
+            _ => self.arithmetic(rhs, i64::checked_add, |lhs, rhs| lhs + rhs),
+        }
+    }
+
+    pub fn subtract(&self, rhs: &Value) -> Option<Value> {
+        self.arithmetic(rhs, i64::checked_sub, |lhs, rhs| lhs - rhs)
+    }
+
+    pub fn multiply(&self, rhs: &Value) -> Option<Value> {
+        self.arithmetic(rhs, i64::checked_mul, |lhs, rhs| lhs * rhs)
+    }
+
+    pub fn divide(&self, rhs: &Value) -> Option<Value> {
+        match (self, rhs) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => {
+                Some(Value::number(*lhs as f64 / *rhs as f64))
+            }
+            _ => self.arithmetic(rhs, |_, _| unreachable!(), |lhs, rhs| lhs / rhs),
+        }
+    }
+
+    /// Key the value stands for when used to index a map, if it can be one.
+    pub fn key(&self) -> Option<MapKey> {
+        match self {
+            Value::Nil => Some(MapKey::Nil),
+            Value::Boolean(value) => Some(MapKey::Boolean(*value)),
+            Value::Integer(value) => Some(MapKey::Integer(*value)),
+            Value::Float(value) => MapKey::float(*value),
+            Value::String(value) => Some(MapKey::String(value.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Converts the value for a native function call. Data values are copied,
+    /// while lists, maps and the VM's other objects are shared with the native as
+    /// foreign values it can inspect and return.
+    pub fn to_runtime(&self) -> RuntimeValue {
+        match self {
+            Value::Integer(value) => RuntimeValue::Integer(*value),
+            Value::Float(value) => RuntimeValue::Float(*value),
+            Value::String(value) => RuntimeValue::String(value.to_string()),
+            Value::Nil => RuntimeValue::Nil,
+            Value::Boolean(value) => RuntimeValue::Boolean(*value),
+            Value::Native(function) => RuntimeValue::Native(Rc::clone(function)),
+            value => RuntimeValue::Foreign(Rc::new(value.clone())),
+        }
+    }
+
+    /// Converts the result of a native function call, failing for values of the
+    /// tree-walker that have no VM counterpart.
+    pub fn from_runtime(value: &RuntimeValue) -> Option<Value> {
+        match value {
+            RuntimeValue::Integer(value) => Some(Value::Integer(*value)),
+            RuntimeValue::Float(value) => Some(Value::Float(*value)),
+            RuntimeValue::String(value) => Some(Value::String(value.as_str().into())),
+            RuntimeValue::Nil => Some(Value::Nil),
+            RuntimeValue::Boolean(value) => Some(Value::Boolean(*value)),
+            RuntimeValue::Native(function) => Some(Value::Native(Rc::clone(function))),
+            RuntimeValue::List(values) => values
+                .borrow()
+                .iter()
+                .map(|value| Value::from_runtime(value))
+                .collect::<Option<_>>()
+                .map(|values| Value::List(Rc::new(RefCell::new(values)))),
+            RuntimeValue::Map(entries) => entries
+                .borrow()
+                .iter()
+                .map(|(key, (original, value))| {
+                    Some((
+                        key.clone(),
+                        (Value::from_runtime(original)?, Value::from_runtime(value)?),
+                    ))
+                })
+                .collect::<Option<_>>()
+                .map(|entries| Value::Map(Rc::new(RefCell::new(entries)))),
+            RuntimeValue::Foreign(value) => value.as_any().downcast_ref::<Value>().cloned(),
+            _ => None,
+        }
+    }
+
+    /// Name of the value's type as reported by the `type` builtin.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Nil => "nil",
+            Value::Boolean(_) => "boolean",
+            Value::Function(_)
+            | Value::Closure(_)
+            | Value::BoundMethod(_)
+            | Value::Native(_)
+            | Value::ListMethod(_)
+            | Value::MapMethod(_) => "function",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Module(_) => "module",
+        }
+    }
+
+    /// Writes the value as an element of a list or map, with strings quoted.
+    fn display_nested(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{value:?}"),
+            value => write!(f, "{value}"),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Value::Integer(0)) || matches!(self, Value::Float(value) if *value == 0.)
+    }
+
+    /// Applies `integer` to two integers, falling back to `float` when it overflows
+    /// or either operand is a float.
+    fn arithmetic(
+        &self,
+        rhs: &Value,
+        integer: impl Fn(i64, i64) -> Option<i64>,
+        float: impl Fn(f64, f64) -> f64,
+    ) -> Option<Value> {
+        match (self, rhs) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => Some(match integer(*lhs, *rhs) {
+                Some(value) => Value::Integer(value),
+                None => Value::Float(float(*lhs as f64, *rhs as f64)),
+            }),
+            (Value::Integer(lhs), Value::Float(rhs)) => {
+                Some(Value::Float(float(*lhs as f64, *rhs)))
+            }
+            (Value::Float(lhs), Value::Integer(rhs)) => {
+                Some(Value::Float(float(*lhs, *rhs as f64)))
+            }
+            (Value::Float(lhs), Value::Float(rhs)) => Some(Value::Float(float(*lhs, *rhs))),
+            _ => None,
+        }
+    }
+}
+
+impl ForeignValue for Value {
+    fn type_name(&self) -> &'static str {
+        Value::type_name(self)
+    }
+
+    fn length(&self) -> Option<usize> {
+        match self {
+            Value::List(values) => Some(values.borrow().len()),
+            Value::Map(entries) => Some(entries.borrow().len()),
+            _ => None,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => lhs == rhs,
+            (Value::Integer(lhs), Value::Float(rhs)) => *lhs as f64 == *rhs,
+            (Value::Float(lhs), Value::Integer(rhs)) => *lhs == *rhs as f64,
+            (Value::Float(lhs), Value::Float(rhs)) => lhs == rhs,
+            (Value::String(lhs), Value::String(rhs)) => lhs == rhs,
+            (Value::Boolean(lhs), Value::Boolean(rhs)) => lhs == rhs,
+            (Value::Nil, Value::Nil) => true,
+            (Value::Class(lhs), Value::Class(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Value::Instance(lhs), Value::Instance(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Value::Native(lhs), Value::Native(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Value::List(lhs), Value::List(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Value::Map(lhs), Value::Map(rhs)) => Rc::ptr_eq(lhs, rhs),
+            (Value::Module(lhs), Value::Module(rhs)) => Rc::ptr_eq(lhs, rhs),
+            _ => false,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => lhs.partial_cmp(rhs),
+            (Value::Integer(lhs), Value::Float(rhs)) => (*lhs as f64).partial_cmp(rhs),
+            (Value::Float(lhs), Value::Integer(rhs)) => lhs.partial_cmp(&(*rhs as f64)),
+            (Value::Float(lhs), Value::Float(rhs)) => lhs.partial_cmp(rhs),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{value}"),
+            Value::Float(value) => write!(f, "{value}"),
+            Value::Boolean(value) => write!(f, "{value}"),
+            Value::String(value) => write!(f, "{value}"),
+            Value::Nil => write!(f, "nil"),
+            Value::Function(_) | Value::Closure(_) | Value::BoundMethod(_) => {
+                write!(f, "[callable]")
+            }
+            Value::Class(class) => write!(f, "[class {}]", class.name),
+            Value::Instance(instance) => write!(f, "[{} instance]", instance.class.name),
+            Value::Native(function) => write!(f, "[native {}]", function.name()),
+            Value::ListMethod(bound) => write!(f, "[native {}]", bound.method.name()),
+            Value::MapMethod(bound) => write!(f, "[native {}]", bound.method.name()),
+            Value::Module(module) => write!(f, "[module {}]", module.name),
+            Value::List(values) => list::display(
+                f,
+                Rc::as_ptr(values) as *const (),
+                values.borrow().iter(),
+                |f, value| value.display_nested(f),
+            ),
+            Value::Map(entries) => map::display(
+                f,
+                Rc::as_ptr(entries) as *const (),
+                &entries.borrow(),
+                |f, value| value.display_nested(f),
+            ),
+        }
+    }
+}
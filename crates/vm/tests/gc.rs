@@ -0,0 +1,80 @@
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{runtime::io::SharedBuffer, Runtime};
+use vm::{compiler::Compiler, Vm};
+
+fn run(vm: &mut Vm, source: &str) {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+    Resolver::new().run(&program).unwrap();
+
+    let function = Compiler::new().compile(&program).unwrap();
+    vm.run(function).unwrap();
+}
+
+/// Creates `count` garbage cycles of the kind built by `body`, then forgets them.
+fn leak(vm: &mut Vm, body: &str, count: usize) {
+    run(
+        vm,
+        &format!(
+            "{{ fun cycle() {{ {body} }} for (var i = 0; i < {count}; i = i + 1) {{ cycle(); }} }}"
+        ),
+    );
+}
+
+#[test]
+fn cycles_are_reclaimed() {
+    let cycles = [
+        // list containing itself
+        "var a = []; a.push(a);",
+        // lists and maps referring to each other
+        "var a = []; var m = {\"a\": a}; a.push(m);",
+        // instance holding itself in a field
+        "class Node {} var node = Node(); node.next = node;",
+        // instance holding one of its own bound methods
+        "class Node { m() {} } var node = Node(); node.m = node.m;",
+        // local function capturing the variable that holds it
+        "fun inner() { return inner; }",
+    ];
+
+    for body in cycles {
+        let mut vm = Vm::with_host(Runtime::with_output(std::io::sink()));
+        vm.collect_garbage();
+        let baseline = vm.gc_stats().objects;
+
+        leak(&mut vm, body, 1000);
+        vm.collect_garbage();
+
+        let stats = vm.gc_stats();
+        assert!(stats.collected >= 1000, "{body}: collected {stats:?}");
+        assert_eq!(stats.objects, baseline, "{body}");
+    }
+}
+
+#[test]
+fn reachable_cycles_are_kept() {
+    let output = SharedBuffer::new();
+    let mut vm = Vm::with_host(Runtime::with_output(output.clone()));
+
+    run(&mut vm, "var a = [1]; a.push(a); var b = a;");
+    vm.collect_garbage();
+    run(&mut vm, "print a;");
+
+    assert_eq!(output.contents(), "[1, [...]]\n");
+}
+
+#[test]
+fn collections_run_as_objects_are_allocated() {
+    let mut vm = Vm::with_host(Runtime::with_output(std::io::sink()));
+
+    leak(&mut vm, "var a = []; a.push(a);", 10_000);
+
+    let stats = vm.gc_stats();
+    assert!(stats.collections > 0);
+    // without collections every list would still be tracked
+    assert!(stats.objects < 10_000, "{stats:?}");
+}
@@ -0,0 +1,50 @@
+use lexer::scanner::Scanner;
+use parser::Parser;
+use resolver::Resolver;
+use runtime::{runtime::limits::Limits, Runtime};
+use vm::{compiler::Compiler, Vm};
+
+/// Compiles and runs `source` on a VM whose host enforces `limits`, returning the
+/// name of the error it stops with.
+fn run(limits: Limits, source: &str) -> Result<(), &'static str> {
+    let scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens().unwrap();
+
+    let (program, errors) = Parser::new(scanner.tokens()).run();
+    assert!(errors.is_empty(), "{errors:?}");
+    Resolver::new().run(&program).unwrap();
+
+    let host = Runtime::with_output(std::io::sink());
+    host.set_limits(limits);
+    host.reset_usage();
+
+    let function = Compiler::new().compile(&program).unwrap();
+    Vm::with_host(host)
+        .run(function)
+        .map_err(|error| error.source().name())
+}
+
+#[test]
+fn memory_limit_is_rejected() {
+    let limits = Limits {
+        max_memory: Some(1 << 20),
+        ..Limits::default()
+    };
+
+    assert_eq!(run(limits, "print 1;"), Err("UnsupportedLimit"));
+}
+
+#[test]
+fn step_budget_and_call_depth_apply() {
+    let budget = Limits {
+        max_steps: Some(10_000),
+        ..Limits::default()
+    };
+    assert_eq!(run(budget, "while (true) {}"), Err("BudgetExhausted"));
+
+    let depth = Limits {
+        max_call_depth: 64,
+        ..Limits::default()
+    };
+    assert_eq!(run(depth, "fun f() { f(); } f();"), Err("StackOverflow"));
+}